@@ -0,0 +1,814 @@
+use crate::{localization::*, output::*};
+use anyhow::{Context, Result, bail};
+use glob::{MatchOptions, Pattern};
+use rvpacker_lib::types::EngineType;
+use serde::Serialize;
+use std::{
+    fs::{File, create_dir_all, read_dir, remove_file, rename},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+};
+use tracing::{info, warn};
+use xxhash_rust::xxh3::xxh3_64;
+
+const ARCHIVE_HEADER: &[u8; 7] = b"RGSSAD\0";
+
+/// Key, that XP and VX archives start with.
+const OLDER_KEY: u32 = 0xDEADCAFE;
+
+pub const ARCHIVE_VERSIONS: [&str; 3] = ["xp", "vx", "vxace"];
+
+/// RGSS archive format.
+///
+/// XP and VX archives share the same format, and only differ in the file
+/// extension.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArchiveVersion {
+    XP,
+    VX,
+    VXAce,
+}
+
+impl ArchiveVersion {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "xp" => Some(Self::XP),
+            "vx" => Some(Self::VX),
+            "vxace" => Some(Self::VXAce),
+            _ => None,
+        }
+    }
+
+    pub const fn from_engine_type(engine_type: EngineType) -> Option<Self> {
+        match engine_type {
+            EngineType::XP => Some(Self::XP),
+            EngineType::VX => Some(Self::VX),
+            EngineType::VXAce => Some(Self::VXAce),
+            _ => None,
+        }
+    }
+
+    /// Detects the version from the extensions of the files, that are going
+    /// to be packed.
+    pub fn from_files(files: &[(String, PathBuf)]) -> Option<Self> {
+        [
+            ("rvdata2", Self::VXAce),
+            ("rvdata", Self::VX),
+            ("rxdata", Self::XP),
+        ]
+        .into_iter()
+        .find(|(extension, _)| {
+            files.iter().any(|(name, _)| {
+                Path::new(name)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+            })
+        })
+        .map(|(_, version)| version)
+    }
+
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::XP => "Game.rgssad",
+            Self::VX => "Game.rgss2a",
+            Self::VXAce => "Game.rgss3a",
+        }
+    }
+}
+
+/// Advances the key of XP and VX archives, and the key of the file data.
+const fn next_key(key: u32) -> u32 {
+    key.wrapping_mul(7).wrapping_add(3)
+}
+
+/// Size of the chunks, that file data is read and written in. Must be a
+/// multiple of 4, so the key advances the same way across chunks.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Copies `size` bytes from `reader` to `writer`, encrypting or decrypting
+/// them on the way, since file data is a plain XOR.
+fn copy_crypted<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    size: u32,
+    mut key: u32,
+) -> io::Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE.min(size as usize)];
+    let mut remaining = size as usize;
+
+    while remaining > 0 {
+        let chunk = &mut buffer[..CHUNK_SIZE.min(remaining)];
+        reader.read_exact(chunk)?;
+
+        for bytes in chunk.chunks_mut(4) {
+            for (byte, key_byte) in bytes.iter_mut().zip(key.to_le_bytes()) {
+                *byte ^= key_byte;
+            }
+
+            key = next_key(key);
+        }
+
+        writer.write_all(chunk)?;
+        remaining -= chunk.len();
+    }
+
+    Ok(())
+}
+
+/// File, stored in the archive.
+pub struct ArchiveEntry {
+    /// Path of the file with `/` separators.
+    pub name: String,
+    pub size: u32,
+    offset: u64,
+    key: u32,
+}
+
+impl ArchiveEntry {
+    /// Returns the path, that the entry is extracted to, or `None`, if its
+    /// name points outside of `output_dir`.
+    pub fn output_path(&self, output_dir: &Path) -> Option<PathBuf> {
+        let name = Path::new(&self.name);
+
+        name.components()
+            .all(|component| matches!(component, Component::Normal(_)))
+            .then(|| output_dir.join(name))
+    }
+}
+
+/// Reads the table of contents, reporting a truncated archive instead of
+/// unexpected EOF.
+struct TableReader<'a, R> {
+    reader: &'a mut R,
+    localization: &'a Localization,
+}
+
+impl<R: Read + Seek> TableReader<'_, R> {
+    fn read_bytes(&mut self, count: usize) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.reader
+            .by_ref()
+            .take(count as u64)
+            .read_to_end(&mut bytes)?;
+
+        if bytes.len() != count {
+            bail!("{}", self.localization.archive_truncated_msg);
+        }
+
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn position(&mut self) -> Result<u64> {
+        Ok(self.reader.stream_position()?)
+    }
+}
+
+/// RGSS archive with decrypted table of contents. File data is only read,
+/// when the entries are extracted.
+pub struct Archive<R> {
+    reader: R,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl Archive<BufReader<File>> {
+    /// Opens the archive file and parses its table of contents.
+    pub fn open(path: &Path, localization: &Localization) -> Result<Self> {
+        File::open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| Self::parse(BufReader::new(file), localization))
+            .with_context(|| path.display().to_string())
+    }
+}
+
+impl<R: Read + Seek> Archive<R> {
+    /// Parses the table of contents of the archive, without reading the
+    /// files.
+    pub fn parse(mut reader: R, localization: &Localization) -> Result<Self> {
+        let length = reader.seek(SeekFrom::End(0))?;
+        reader.rewind()?;
+
+        let mut table = TableReader {
+            reader: &mut reader,
+            localization,
+        };
+
+        if table.read_bytes(7).ok().as_deref() != Some(ARCHIVE_HEADER) {
+            bail!("{}", localization.archive_invalid_header_msg);
+        }
+
+        let mut entries = Vec::new();
+
+        match table.read_bytes(1)?[0] {
+            1 => {
+                let mut key = OLDER_KEY;
+
+                while table.position()? < length {
+                    let name_length = (table.read_u32()? ^ key) as usize;
+                    key = next_key(key);
+
+                    let mut name = table.read_bytes(name_length)?;
+
+                    for byte in &mut name {
+                        *byte ^= key as u8;
+                        key = next_key(key);
+                    }
+
+                    let size = table.read_u32()? ^ key;
+                    key = next_key(key);
+
+                    let offset = table.position()?;
+
+                    if length < offset + u64::from(size) {
+                        bail!("{}", localization.archive_truncated_msg);
+                    }
+
+                    entries.push(ArchiveEntry {
+                        name: String::from_utf8_lossy(&name).replace('\\', "/"),
+                        size,
+                        offset,
+                        key,
+                    });
+
+                    table.reader.seek(SeekFrom::Current(i64::from(size)))?;
+                }
+            }
+            3 => {
+                let key = table.read_u32()?.wrapping_mul(9).wrapping_add(3);
+                let key_bytes = key.to_le_bytes();
+
+                loop {
+                    let offset = u64::from(table.read_u32()? ^ key);
+
+                    if offset == 0 {
+                        break;
+                    }
+
+                    let size = table.read_u32()? ^ key;
+                    let entry_key = table.read_u32()? ^ key;
+                    let name_length = (table.read_u32()? ^ key) as usize;
+
+                    let mut name = table.read_bytes(name_length)?;
+
+                    for (i, byte) in name.iter_mut().enumerate() {
+                        *byte ^= key_bytes[i % 4];
+                    }
+
+                    if length < offset + u64::from(size) {
+                        bail!("{}", localization.archive_truncated_msg);
+                    }
+
+                    entries.push(ArchiveEntry {
+                        name: String::from_utf8_lossy(&name).replace('\\', "/"),
+                        size,
+                        offset,
+                        key: entry_key,
+                    });
+                }
+            }
+            _ => bail!("{}", localization.archive_invalid_header_msg),
+        }
+
+        Ok(Self { reader, entries })
+    }
+
+    /// Extracts the entries, that match `globs`, to `output_dir`. Existing
+    /// files are skipped, unless `force` is set. Each entry is logged before
+    /// extraction, if `progress` is set.
+    ///
+    /// Returns the count of extracted files.
+    pub fn extract(
+        &mut self,
+        globs: &[Pattern],
+        output_dir: &Path,
+        force: bool,
+        progress: bool,
+        verbose: bool,
+        loc: &Localization,
+    ) -> Result<usize> {
+        let entries: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| matches_globs(&self.entries[i].name, globs))
+            .collect();
+        let mut extracted = 0;
+
+        for (number, &i) in entries.iter().enumerate() {
+            let entry = &self.entries[i];
+
+            if progress {
+                info!(
+                    "[{}/{}] {} ({})",
+                    number + 1,
+                    entries.len(),
+                    entry.name,
+                    format_size(u64::from(entry.size))
+                );
+            }
+
+            let Some(output_path) = entry.output_path(output_dir) else {
+                warn!("{}: {}", entry.name, loc.archive_unsafe_entry_msg);
+                continue;
+            };
+
+            if output_path.exists() && !force {
+                warn!("{}: {}", entry.name, loc.archive_file_exists_msg);
+                continue;
+            }
+
+            if let Some(parent) = output_path.parent() {
+                create_dir_all(parent)?;
+            }
+
+            let mut writer = BufWriter::new(File::create(&output_path)?);
+            self.reader.seek(SeekFrom::Start(entry.offset))?;
+            copy_crypted(&mut self.reader, &mut writer, entry.size, entry.key)
+                .and_then(|()| writer.flush())
+                .with_context(|| output_path.display().to_string())?;
+            extracted += 1;
+
+            if verbose {
+                info!("{}: {}", entry.name, loc.archive_extracted_file_msg);
+            }
+        }
+
+        Ok(extracted)
+    }
+}
+
+/// Whether the file is an RGSS archive, judging by its extension.
+fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        ["rgssad", "rgss2a", "rgss3a"]
+            .iter()
+            .any(|archive_ext| ext.eq_ignore_ascii_case(archive_ext))
+    })
+}
+
+/// Compiles `--glob` patterns. Entry names are matched case-insensitively,
+/// since RPG Maker resolves them case-insensitively too.
+pub fn parse_globs(
+    globs: &[String],
+    localization: &Localization,
+) -> Result<Vec<Pattern>> {
+    globs
+        .iter()
+        .map(|glob| {
+            Pattern::new(glob).with_context(|| {
+                format!("{glob}: {}", localization.invalid_glob_msg)
+            })
+        })
+        .collect()
+}
+
+/// Whether the name matches any of the patterns. Every name matches, if there
+/// are no patterns.
+pub fn matches_globs(name: &str, globs: &[Pattern]) -> bool {
+    let options = MatchOptions {
+        case_sensitive: false,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    globs.is_empty()
+        || globs.iter().any(|glob| glob.matches_with(name, options))
+}
+
+/// Collects every file in `dir` recursively, along with its archive entry
+/// name, sorted by name. RGSS archives themselves are skipped, so packing the
+/// game directory doesn't put the previous archive into the new one.
+pub fn collect_pack_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    fn collect(
+        root: &Path,
+        dir: &Path,
+        files: &mut Vec<(String, PathBuf)>,
+    ) -> Result<()> {
+        for entry in read_dir(dir)?.flatten() {
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                collect(root, &path, files)?;
+            } else if is_archive(&path) {
+                continue;
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                let name = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((name, path));
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(dir, dir, &mut files)?;
+    files.sort_unstable();
+    Ok(files)
+}
+
+/// Packs `files`, given as entry names with the paths of their content, to
+/// the archive of the given version.
+///
+/// Keys are derived from the entry names, so packing the same files always
+/// produces the same archive.
+fn pack<W: Write>(
+    files: &[(String, PathBuf)],
+    version: ArchiveVersion,
+    writer: &mut W,
+    localization: &Localization,
+) -> Result<()> {
+    let get_size = |path: &Path| -> Result<u32> {
+        u32::try_from(path.metadata()?.len()).with_context(|| {
+            format!("{}: {}", path.display(), localization.file_too_large_msg)
+        })
+    };
+
+    // RPG Maker expects backslash separators.
+    let names: Vec<Vec<u8>> = files
+        .iter()
+        .map(|(name, _)| name.replace('/', "\\").into_bytes())
+        .collect();
+
+    writer.write_all(ARCHIVE_HEADER)?;
+
+    match version {
+        ArchiveVersion::XP | ArchiveVersion::VX => {
+            writer.write_all(&[1])?;
+
+            let mut key = OLDER_KEY;
+
+            for (name, (_, path)) in names.iter().zip(files) {
+                let size = get_size(path)?;
+
+                writer.write_all(&(name.len() as u32 ^ key).to_le_bytes())?;
+                key = next_key(key);
+
+                for byte in name {
+                    writer.write_all(&[byte ^ key as u8])?;
+                    key = next_key(key);
+                }
+
+                writer.write_all(&(size ^ key).to_le_bytes())?;
+                key = next_key(key);
+
+                let mut reader = BufReader::new(File::open(path)?);
+                copy_crypted(&mut reader, writer, size, key)?;
+            }
+        }
+        ArchiveVersion::VXAce => {
+            writer.write_all(&[3])?;
+
+            let seed = names
+                .iter()
+                .fold(0u64, |hash, name| xxh3_64(name) ^ hash.rotate_left(1))
+                as u32;
+            let key = seed.wrapping_mul(9).wrapping_add(3);
+            let key_bytes = key.to_le_bytes();
+
+            writer.write_all(&seed.to_le_bytes())?;
+
+            // Header, seed, every entry and the terminating entry.
+            let table_size: usize =
+                names.iter().map(|name| 16 + name.len()).sum();
+            let mut offset = 8 + 4 + table_size + 16;
+            let mut entries = Vec::with_capacity(files.len());
+
+            for (name, (_, path)) in names.iter().zip(files) {
+                let size = get_size(path)?;
+                let entry_key = xxh3_64(name) as u32;
+                let offset_u32 = u32::try_from(offset)
+                    .context(localization.file_too_large_msg)?;
+
+                for value in [offset_u32, size, entry_key, name.len() as u32] {
+                    writer.write_all(&(value ^ key).to_le_bytes())?;
+                }
+
+                let name: Vec<u8> = name
+                    .iter()
+                    .enumerate()
+                    .map(|(i, byte)| byte ^ key_bytes[i % 4])
+                    .collect();
+                writer.write_all(&name)?;
+
+                entries.push((size, entry_key));
+                offset += size as usize;
+            }
+
+            for _ in 0..4 {
+                writer.write_all(&key.to_le_bytes())?;
+            }
+
+            for ((_, path), (size, entry_key)) in files.iter().zip(entries) {
+                let mut reader = BufReader::new(File::open(path)?);
+                copy_crypted(&mut reader, writer, size, entry_key)?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Packs `files` to the archive at `archive_path`.
+///
+/// Archive is written to a temporary file first, so a failure doesn't leave a
+/// broken archive in place of the original.
+pub fn pack_to_file(
+    files: &[(String, PathBuf)],
+    version: ArchiveVersion,
+    archive_path: &Path,
+    localization: &Localization,
+) -> Result<()> {
+    let temp_path = archive_path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&temp_path)?);
+
+    if let Err(err) = pack(files, version, &mut writer, localization) {
+        drop(writer);
+        let _ = remove_file(&temp_path);
+        return Err(err);
+    }
+
+    drop(writer);
+    rename(&temp_path, archive_path)?;
+    Ok(())
+}
+
+/// Formats the size in bytes with binary units.
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = size as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{size} {}", UNITS[0])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[derive(Serialize)]
+struct EntryRecord<'a> {
+    name: Option<&'a str>,
+    count: Option<usize>,
+    size: u64,
+}
+
+/// Logs every entry with its size and the total, or prints them as JSON
+/// events to stdout.
+pub fn report_entries(
+    entries: &[&ArchiveEntry],
+    loc: &Localization,
+    output_format: OutputFormat,
+) {
+    let total: u64 = entries.iter().map(|entry| u64::from(entry.size)).sum();
+
+    if output_format.is_json() {
+        for entry in entries {
+            let record = EntryRecord {
+                name: Some(&entry.name),
+                count: None,
+                size: u64::from(entry.size),
+            };
+
+            emit_event("entry", &record, None);
+        }
+
+        let record = EntryRecord {
+            name: None,
+            count: Some(entries.len()),
+            size: total,
+        };
+
+        emit_event("total", &record, None);
+        return;
+    }
+
+    for entry in entries {
+        info!("{} ({})", entry.name, format_size(u64::from(entry.size)));
+    }
+
+    info!(
+        "{}: {} {}, {}",
+        loc.stats_total_label,
+        entries.len(),
+        loc.archive_entries_label,
+        format_size(total)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::localization::Language;
+    use std::{
+        fs::{read, write},
+        io::Cursor,
+    };
+
+    /// Files with contents of every length modulo 4, since XOR keys are
+    /// applied per four bytes.
+    const FILES: [(&str, usize); 4] = [
+        ("Data/Map001.rxdata", 1001),
+        ("Data/System.rxdata", 4),
+        ("Graphics/Pictures/Empty.png", 0),
+        ("Graphics/Pictures/Title.png", 70_002),
+    ];
+
+    const VERSIONS: [ArchiveVersion; 3] = [
+        ArchiveVersion::XP,
+        ArchiveVersion::VX,
+        ArchiveVersion::VXAce,
+    ];
+
+    /// Creates the files in `dir`, and returns them along with their entry
+    /// names and contents.
+    fn create_files(dir: &Path) -> Vec<(String, PathBuf, Vec<u8>)> {
+        FILES
+            .into_iter()
+            .map(|(name, size)| {
+                let path = dir.join(name);
+                let content: Vec<u8> =
+                    (0..size).map(|i| (i * 7 + name.len()) as u8).collect();
+
+                create_dir_all(path.parent().unwrap()).unwrap();
+                write(&path, &content).unwrap();
+                (name.to_owned(), path, content)
+            })
+            .collect()
+    }
+
+    fn pack_files(
+        files: &[(String, PathBuf, Vec<u8>)],
+        version: ArchiveVersion,
+    ) -> Vec<u8> {
+        let files: Vec<(String, PathBuf)> = files
+            .iter()
+            .map(|(name, path, _)| (name.clone(), path.clone()))
+            .collect();
+        let mut archive = Vec::new();
+
+        pack(
+            &files,
+            version,
+            &mut archive,
+            &Localization::new(Language::English),
+        )
+        .unwrap();
+        archive
+    }
+
+    #[test]
+    fn round_trips_every_version() {
+        let localization = Localization::new(Language::English);
+        let dir = tempfile::tempdir().unwrap();
+        let files = create_files(&dir.path().join("game"));
+
+        for version in VERSIONS {
+            let output_dir = dir.path().join(version.file_name());
+            let mut archive = Archive::parse(
+                Cursor::new(pack_files(&files, version)),
+                &localization,
+            )
+            .unwrap();
+
+            let names: Vec<&str> = archive
+                .entries
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect();
+            assert_eq!(names, FILES.map(|(name, _)| name));
+
+            let extracted = archive
+                .extract(&[], &output_dir, false, false, false, &localization)
+                .unwrap();
+            assert_eq!(extracted, FILES.len());
+
+            for (name, _, content) in &files {
+                assert_eq!(&read(output_dir.join(name)).unwrap(), content);
+            }
+        }
+    }
+
+    /// The reference implementation, `rpgmad-lib`, must extract the packed
+    /// archives to the same files.
+    #[test]
+    fn reference_extracts_packed_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = create_files(&dir.path().join("game"));
+
+        for version in VERSIONS {
+            let output_dir = dir.path().join(version.file_name());
+            rpgmad_lib::Decrypter::new()
+                .extract(&pack_files(&files, version), &output_dir)
+                .unwrap();
+
+            // `rpgmad-lib` stops at the end of XP and VX archives before
+            // storing the last entry, so it never extracts the last file.
+            let extracted = if version == ArchiveVersion::VXAce {
+                &files[..]
+            } else {
+                &files[..files.len() - 1]
+            };
+
+            // Entry names are written as is, so on Unix the backslash
+            // separators end up in the file names.
+            for (name, _, content) in extracted {
+                let path = if cfg!(windows) {
+                    output_dir.join(name)
+                } else {
+                    output_dir.join(name.replace('/', "\\"))
+                };
+
+                assert_eq!(
+                    &read(&path).unwrap(),
+                    content,
+                    "{}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    /// Fixtures are built independently of `pack`, and `rpgmad-lib` must
+    /// extract the same files from them.
+    #[test]
+    fn extracts_fixtures_like_reference() {
+        let localization = Localization::new(Language::English);
+        let dir = tempfile::tempdir().unwrap();
+        let fixtures: [(&str, &[u8]); 2] = [
+            ("xp", include_bytes!("../tests/fixtures/archive.rgssad")),
+            ("vxace", include_bytes!("../tests/fixtures/archive.rgss3a")),
+        ];
+
+        for (name, fixture) in fixtures {
+            let output_dir = dir.path().join(name);
+            let reference_dir = dir.path().join(format!("{name}-reference"));
+            let mut archive =
+                Archive::parse(Cursor::new(fixture), &localization).unwrap();
+
+            let names: Vec<&str> = archive
+                .entries
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect();
+            assert_eq!(
+                names,
+                [
+                    "Data/Scripts.rxdata",
+                    "Audio/SE/Click.ogg",
+                    "Data/Last.rxdata"
+                ]
+            );
+
+            archive
+                .extract(&[], &output_dir, false, false, false, &localization)
+                .unwrap();
+            rpgmad_lib::Decrypter::new()
+                .extract(fixture, &reference_dir)
+                .unwrap();
+
+            // On Unix, the reference keeps backslashes in the file names.
+            for (name, path) in collect_pack_files(&reference_dir).unwrap() {
+                let name = name.replace('\\', "/");
+
+                assert_eq!(
+                    read(output_dir.join(&name)).unwrap(),
+                    read(path).unwrap(),
+                    "{name}"
+                );
+            }
+
+            assert_eq!(
+                read(output_dir.join("Data/Last.rxdata")).unwrap(),
+                b"last entry"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_archive() {
+        let localization = Localization::new(Language::English);
+        let dir = tempfile::tempdir().unwrap();
+        let files = create_files(&dir.path().join("game"));
+
+        for version in VERSIONS {
+            let mut archive = pack_files(&files, version);
+            archive.truncate(archive.len() - 1);
+
+            assert!(
+                Archive::parse(Cursor::new(archive), &localization).is_err()
+            );
+        }
+    }
+}
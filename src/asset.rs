@@ -0,0 +1,703 @@
+use crate::{localization::*, output::*};
+use anyhow::{Context, Result};
+use asset_decrypter::{Decrypter, KEY_LENGTH};
+use rvpacker_lib::read_to_string_without_bom;
+use serde::Serialize;
+use serde_json::{Value, from_str, to_string};
+use std::{
+    fs::{File, create_dir_all, read, read_dir, remove_dir_all, rename, write},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+/// Directory in the game root, that re-encrypted assets are staged in.
+const REKEY_DIR: &str = ".rvpacker-rekey";
+
+/// File in [`REKEY_DIR`], that marks the staged assets as complete.
+const REKEY_COMMIT_FILE: &str = "commit";
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Header, that every encrypted asset starts with.
+const ENCRYPTED_HEADER: &[u8; 5] = b"RPGMV";
+
+/// Length of the header of encrypted assets, followed by the encrypted
+/// header of the original file.
+const HEADER_LENGTH: usize = 16;
+
+/// Extensions of the encrypted MV and MZ assets.
+pub const ENCRYPTED_EXTENSIONS: [&str; 6] =
+    ["rpgmvp", "rpgmvo", "rpgmvm", "png_", "ogg_", "m4a_"];
+
+/// Extensions of the encrypted images, that the key can be derived from.
+pub const ENCRYPTED_IMAGE_EXTENSIONS: [&str; 2] = ["rpgmvp", "png_"];
+
+/// Directories of the game, that contain encrypted assets.
+const GAME_ASSET_DIRS: [&str; 2] = ["img", "audio"];
+
+/// Assets, that MV loads unencrypted even in encrypted games, relative to the
+/// game root.
+pub const MV_UNENCRYPTED_ASSETS: [&str; 1] = ["img/system/Window.png"];
+
+/// Extensions of the assets, that can be encrypted.
+pub const PLAIN_EXTENSIONS: [&str; 3] = ["png", "ogg", "m4a"];
+
+/// Returns the extension of the decrypted asset.
+pub fn decrypted_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "rpgmvp" | "png_" => Some("png"),
+        "rpgmvo" | "ogg_" => Some("ogg"),
+        "rpgmvm" | "m4a_" => Some("m4a"),
+        _ => None,
+    }
+}
+
+/// Returns the extension of the asset, encrypted for the given engine.
+pub fn encrypted_extension(
+    engine: &str,
+    extension: &str,
+) -> Option<&'static str> {
+    match (engine, extension) {
+        ("mv", "png") => Some("rpgmvp"),
+        ("mv", "ogg") => Some("rpgmvo"),
+        ("mv", "m4a") => Some("rpgmvm"),
+        ("mz", "png") => Some("png_"),
+        ("mz", "ogg") => Some("ogg_"),
+        ("mz", "m4a") => Some("m4a_"),
+        _ => None,
+    }
+}
+
+/// Collects the files with the given extensions from `dir` and its
+/// subdirectories, as sorted paths relative to `dir`.
+pub fn collect_assets(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>> {
+    fn collect(
+        root: &Path,
+        dir: &Path,
+        extensions: &[&str],
+        files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        for entry in read_dir(dir)?.flatten() {
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                collect(root, &path, extensions, files)?;
+            } else if path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.contains(&ext))
+            {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(dir, dir, extensions, &mut files)?;
+    files.sort_unstable();
+    Ok(files)
+}
+
+/// Collects the assets with the given extensions from `img` and `audio`
+/// directories of the game, as sorted paths relative to `root`, since the
+/// engine only encrypts them.
+pub fn collect_game_assets(
+    root: &Path,
+    extensions: &[&str],
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for dir in GAME_ASSET_DIRS {
+        let dir_path = root.join(dir);
+
+        if dir_path.exists() {
+            files.extend(
+                collect_assets(&dir_path, extensions)?
+                    .into_iter()
+                    .map(|path| Path::new(dir).join(path)),
+            );
+        }
+    }
+
+    Ok(files)
+}
+
+/// Whether the key consists of 32 hexadecimal characters, as the decrypter
+/// expects.
+pub fn is_valid_key(key: &str) -> bool {
+    key.len() == KEY_LENGTH && key.chars().all(|char| char.is_ascii_hexdigit())
+}
+
+/// Generates a new random key.
+pub fn generate_key() -> String {
+    format!("{:032x}", fastrand::u128(..))
+}
+
+/// Finds `System.json` of the game, that `dir` belongs to, by looking in
+/// `data` and `www/data` of `dir` and its parents.
+pub fn find_system_json(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+    dir.ancestors()
+        .flat_map(|ancestor| {
+            [
+                ancestor.join("data/System.json"),
+                ancestor.join("www/data/System.json"),
+            ]
+        })
+        .find(|path| path.is_file())
+}
+
+/// Reads `encryptionKey` from `System.json`. Returns `None`, if the game
+/// has no valid key.
+pub fn read_encryption_key(system_json: &Path) -> Result<Option<String>> {
+    let system: Value = from_str(&read_to_string_without_bom(system_json)?)?;
+
+    Ok(system["encryptionKey"]
+        .as_str()
+        .filter(|key| is_valid_key(key))
+        .map(str::to_lowercase))
+}
+
+/// Turns on `hasEncryptedImages` and `hasEncryptedAudio` of `System.json` at
+/// `path` and sets its `encryptionKey`, if `key` is passed, or turns them off
+/// otherwise, and writes it to `output_path`.
+pub fn set_encrypted_assets(
+    path: &Path,
+    output_path: &Path,
+    key: Option<&str>,
+) -> Result<()> {
+    update_system_json(path, output_path, |system| {
+        system["hasEncryptedImages"] = Value::Bool(key.is_some());
+        system["hasEncryptedAudio"] = Value::Bool(key.is_some());
+
+        if let Some(key) = key {
+            system["encryptionKey"] = Value::String(key.to_owned());
+        }
+    })
+}
+
+fn update_system_json(
+    path: &Path,
+    output_path: &Path,
+    update: impl FnOnce(&mut Value),
+) -> Result<()> {
+    let system = updated_system_json(path, update)?;
+
+    if let Some(parent) = output_path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    write(output_path, system)?;
+    Ok(())
+}
+
+/// Returns the content of `System.json` at `path`, changed by `update`.
+fn updated_system_json(
+    path: &Path,
+    update: impl FnOnce(&mut Value),
+) -> Result<String> {
+    let mut system: Value = from_str(&read_to_string_without_bom(path)?)?;
+    update(&mut system);
+    Ok(to_string(&system)?)
+}
+
+/// Reads `hasEncryptedImages` and `hasEncryptedAudio` of `System.json`.
+pub fn read_encrypted_assets(system_json: &Path) -> Result<(bool, bool)> {
+    let system: Value = from_str(&read_to_string_without_bom(system_json)?)?;
+
+    Ok((
+        system["hasEncryptedImages"].as_bool().unwrap_or_default(),
+        system["hasEncryptedAudio"].as_bool().unwrap_or_default(),
+    ))
+}
+
+/// Reads at most `length` first bytes of the file.
+fn read_start(path: &Path, length: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(length);
+    File::open(path)?
+        .take(length as u64)
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Whether the data starts with the header of encrypted assets, and can be
+/// decrypted.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.len() >= HEADER_LENGTH && data.starts_with(ENCRYPTED_HEADER)
+}
+
+/// Derives the key from the encrypted PNG, since the header of every PNG is
+/// the same. Returns `None`, if the file is not an encrypted asset.
+pub fn key_from_image(path: &Path) -> Result<Option<String>> {
+    let header = read_start(path, HEADER_LENGTH * 2)?;
+
+    if header.len() < HEADER_LENGTH * 2 || !is_encrypted(&header) {
+        return Ok(None);
+    }
+
+    let mut decrypter = Decrypter::new();
+    decrypter.set_key_from_image(&header);
+    Ok(decrypter.key().map(String::from))
+}
+
+/// Detects the engine of the game, that `dir` belongs to, from the
+/// extensions of its encrypted assets, its core script (`rmmz_core.js` or
+/// `rpg_core.js` in `js` or `www/js`), or the fields of its `System.json`,
+/// since only MZ has `advanced` field.
+///
+/// Returns `"mv"` or `"mz"` with the file, that the engine was detected from.
+pub fn detect_engine(dir: &Path) -> Result<Option<(&'static str, PathBuf)>> {
+    if let Some(path) = collect_assets(dir, &ENCRYPTED_EXTENSIONS)?.first() {
+        let engine = if path
+            .extension()
+            .is_some_and(|ext| ext.to_string_lossy().starts_with("rpgmv"))
+        {
+            "mv"
+        } else {
+            "mz"
+        };
+
+        return Ok(Some((engine, dir.join(path))));
+    }
+
+    let canonical = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+    for ancestor in canonical.ancestors() {
+        for js_dir in [ancestor.join("js"), ancestor.join("www/js")] {
+            for (script, engine) in
+                [("rmmz_core.js", "mz"), ("rpg_core.js", "mv")]
+            {
+                let path = js_dir.join(script);
+
+                if path.is_file() {
+                    return Ok(Some((engine, path)));
+                }
+            }
+        }
+    }
+
+    if let Some(system_json) = find_system_json(dir) {
+        let system: Value =
+            from_str(&read_to_string_without_bom(&system_json)?)?;
+        let engine = if system.get("advanced").is_some() {
+            "mz"
+        } else {
+            "mv"
+        };
+
+        return Ok(Some((engine, system_json)));
+    }
+
+    Ok(None)
+}
+
+/// Where the encryption key was found.
+pub enum KeySource {
+    SystemJson(PathBuf),
+    Image(PathBuf),
+}
+
+/// Finds the encryption key of the game, that `dir` belongs to: from
+/// `encryptionKey` of its `System.json`, or from the header of any encrypted
+/// PNG in `images`.
+pub fn find_key(
+    dir: &Path,
+    images: &[PathBuf],
+) -> Result<Option<(String, KeySource)>> {
+    if let Some(system_json) = find_system_json(dir) {
+        if let Some(key) = read_encryption_key(&system_json)? {
+            return Ok(Some((key, KeySource::SystemJson(system_json))));
+        }
+    }
+
+    for image in images {
+        if let Some(key) = key_from_image(image)? {
+            return Ok(Some((key, KeySource::Image(image.clone()))));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether the decrypted asset starts with the signature of its format,
+/// which is only the case, if it was decrypted with the right key.
+pub fn has_valid_signature(extension: &str, data: &[u8]) -> bool {
+    match decrypted_extension(extension) {
+        Some("png") => data.starts_with(PNG_SIGNATURE),
+        Some("ogg") => data.starts_with(b"OggS"),
+        Some("m4a") => data.get(4..8) == Some(b"ftyp"),
+        _ => false,
+    }
+}
+
+/// Writes the file and waits until it reaches the disk.
+fn write_synced(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Waits until the entries of `dir` and of its subdirectories reach the disk,
+/// so the files, written to them, can't disappear after a crash. Directories
+/// can only be synced on Unix.
+fn sync_dir(dir: &Path) -> Result<()> {
+    if !cfg!(unix) {
+        return Ok(());
+    }
+
+    for entry in read_dir(dir)?.flatten() {
+        if entry.file_type()?.is_dir() {
+            sync_dir(&entry.path())?;
+        }
+    }
+
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Moves every file from `dir` to the same path in `root`.
+fn move_staged(dir: &Path, root: &Path) -> Result<()> {
+    for entry in read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let target = root.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            create_dir_all(&target)?;
+            move_staged(&path, &target)?;
+        } else {
+            rename(&path, &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Finishes re-keying of the game, that was interrupted while the staged
+/// assets were moved to the game, or discards the stage, that was
+/// interrupted before it was complete.
+///
+/// Returns whether interrupted re-keying was finished.
+pub fn finish_rekey(root: &Path) -> Result<bool> {
+    let stage_dir = root.join(REKEY_DIR);
+
+    if !stage_dir.exists() {
+        return Ok(false);
+    }
+
+    let complete = stage_dir.join(REKEY_COMMIT_FILE).exists();
+
+    if complete {
+        move_staged(&stage_dir.join("files"), root)?;
+    }
+
+    remove_dir_all(&stage_dir)?;
+    Ok(complete)
+}
+
+/// Re-encrypts `assets`, given relative to the game `root`, from the key of
+/// `decrypter` to the key of `encrypter`, and sets the new key in
+/// `System.json`.
+///
+/// Every asset is staged first, and the game is only changed, when the stage
+/// is complete, so an interruption leaves either the untouched game, or the
+/// complete stage, that [`finish_rekey`] moves to the game.
+pub fn rekey_assets(
+    root: &Path,
+    assets: &[PathBuf],
+    decrypter: &mut Decrypter,
+    encrypter: &Decrypter,
+    localization: &Localization,
+) -> Result<()> {
+    let stage_dir = root.join(REKEY_DIR);
+    let files_dir = stage_dir.join("files");
+
+    let mut stage = || -> Result<()> {
+        for asset in assets {
+            let data = read(root.join(asset))?;
+            let extension = asset
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default();
+
+            // Re-encrypting assets, that don't decrypt with the key, would
+            // make them unrecoverable.
+            let decrypted = is_encrypted(&data)
+                .then(|| decrypter.decrypt(&data))
+                .filter(|decrypted| has_valid_signature(extension, decrypted))
+                .with_context(|| {
+                    format!(
+                        "{}: {}",
+                        asset.display(),
+                        localization.asset_key_mismatch_msg
+                    )
+                })?;
+
+            write_synced(
+                &files_dir.join(asset),
+                &encrypter.encrypt(&decrypted)?,
+            )?;
+        }
+
+        let system_json = Path::new("data/System.json");
+        let key = encrypter.key().unwrap_or_default();
+
+        let system = updated_system_json(&root.join(system_json), |system| {
+            system["encryptionKey"] = Value::String(key.to_owned());
+        })?;
+        write_synced(&files_dir.join(system_json), system.as_bytes())?;
+
+        // The commit marker must not reach the disk before the stage does.
+        sync_dir(&stage_dir)?;
+        write_synced(&stage_dir.join(REKEY_COMMIT_FILE), &[])?;
+        sync_dir(&stage_dir)
+    };
+
+    if let Err(err) = stage() {
+        let _ = remove_dir_all(&stage_dir);
+        return Err(err);
+    }
+
+    finish_rekey(root)?;
+    Ok(())
+}
+
+/// Problem with an asset, that `asset verify` finds.
+#[derive(Serialize, Clone, Copy, PartialEq)]
+pub enum AssetProblem {
+    Corrupt,
+    KeyMismatch,
+    Unencrypted,
+}
+
+impl AssetProblem {
+    fn message(self, loc: &Localization) -> &'static str {
+        match self {
+            Self::Corrupt => loc.asset_corrupt_msg,
+            Self::KeyMismatch => loc.asset_wrong_key_msg,
+            Self::Unencrypted => loc.asset_unencrypted_msg,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AssetFinding {
+    pub problem: AssetProblem,
+    pub file: String,
+}
+
+impl Report for AssetFinding {
+    const EVENT_TYPE: &str = "finding";
+
+    fn message(&self, loc: &Localization) -> &'static str {
+        self.problem.message(loc)
+    }
+
+    fn log(&self, message: &str, _: &Localization) {
+        warn!("{}: {message}", self.file);
+    }
+}
+
+/// Checks the header of the encrypted asset, and the signature of its data,
+/// decrypted with the key of `decrypter`.
+///
+/// Returns `None`, if the asset decrypts correctly.
+pub fn verify_asset(
+    path: &Path,
+    decrypter: &mut Decrypter,
+) -> Result<Option<AssetProblem>> {
+    // Encrypted header and the start of the data are enough to check the
+    // signature.
+    let data = read_start(path, HEADER_LENGTH * 2)?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    let problem = if !is_encrypted(&data) {
+        // Plain file may have the extension of the encrypted one.
+        if has_valid_signature(extension, &data) {
+            Some(AssetProblem::Unencrypted)
+        } else {
+            Some(AssetProblem::Corrupt)
+        }
+    } else if !has_valid_signature(extension, &decrypter.decrypt(&data)) {
+        Some(AssetProblem::KeyMismatch)
+    } else {
+        None
+    };
+
+    Ok(problem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCALIZATION: Localization = Localization::new(Language::English);
+    const OLD_KEY: &str = "0123456789abcdef0123456789abcdef";
+    const NEW_KEY: &str = "fedcba9876543210fedcba9876543210";
+    const ASSET: &str = "img/pictures/Title.rpgmvp";
+
+    fn decrypter(key: &str) -> Decrypter {
+        let mut decrypter = Decrypter::new();
+        decrypter.set_key_from_str(key).unwrap();
+        decrypter
+    }
+
+    fn png() -> Vec<u8> {
+        [PNG_SIGNATURE.as_slice(), &[7; 40]].concat()
+    }
+
+    /// Creates the game with a single image, encrypted with `key`, and
+    /// `System.json` with that key.
+    fn create_game(root: &Path, key: &str) {
+        create_dir_all(root.join("data")).unwrap();
+        write(
+            root.join("data/System.json"),
+            format!(r#"{{"hasEncryptedImages":true,"encryptionKey":"{key}"}}"#),
+        )
+        .unwrap();
+        write_synced(
+            &root.join(ASSET),
+            &decrypter(key).encrypt(&png()).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn game_key(root: &Path) -> Option<String> {
+        read_encryption_key(&root.join("data/System.json")).unwrap()
+    }
+
+    fn decrypt_asset(root: &Path, key: &str) -> Vec<u8> {
+        decrypter(key).decrypt(&read(root.join(ASSET)).unwrap())
+    }
+
+    #[test]
+    fn rekeys_assets_and_system_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        create_game(root, OLD_KEY);
+
+        rekey_assets(
+            root,
+            &[PathBuf::from(ASSET)],
+            &mut decrypter(OLD_KEY),
+            &decrypter(NEW_KEY),
+            &LOCALIZATION,
+        )
+        .unwrap();
+
+        assert_eq!(decrypt_asset(root, NEW_KEY), png());
+        assert_eq!(game_key(root).as_deref(), Some(NEW_KEY));
+        assert!(!root.join(REKEY_DIR).exists());
+    }
+
+    #[test]
+    fn leaves_game_untouched_on_key_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        create_game(root, OLD_KEY);
+
+        assert!(
+            rekey_assets(
+                root,
+                &[PathBuf::from(ASSET)],
+                &mut decrypter(NEW_KEY),
+                &decrypter(NEW_KEY),
+                &LOCALIZATION,
+            )
+            .is_err()
+        );
+
+        assert_eq!(decrypt_asset(root, OLD_KEY), png());
+        assert_eq!(game_key(root).as_deref(), Some(OLD_KEY));
+        assert!(!root.join(REKEY_DIR).exists());
+    }
+
+    /// Stages the game with the new key, as interrupted re-keying leaves it.
+    fn stage_rekey(root: &Path, commit: bool) {
+        let staged = tempfile::tempdir().unwrap();
+        create_game(staged.path(), NEW_KEY);
+
+        let files_dir = root.join(REKEY_DIR).join("files");
+        create_dir_all(&files_dir).unwrap();
+        move_staged(staged.path(), &files_dir).unwrap();
+
+        if commit {
+            write_synced(&root.join(REKEY_DIR).join(REKEY_COMMIT_FILE), &[])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn finishes_committed_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        create_game(root, OLD_KEY);
+        stage_rekey(root, true);
+
+        assert!(finish_rekey(root).unwrap());
+
+        assert_eq!(decrypt_asset(root, NEW_KEY), png());
+        assert_eq!(game_key(root).as_deref(), Some(NEW_KEY));
+        assert!(!root.join(REKEY_DIR).exists());
+    }
+
+    #[test]
+    fn discards_uncommitted_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        create_game(root, OLD_KEY);
+        stage_rekey(root, false);
+
+        assert!(!finish_rekey(root).unwrap());
+
+        assert_eq!(decrypt_asset(root, OLD_KEY), png());
+        assert_eq!(game_key(root).as_deref(), Some(OLD_KEY));
+        assert!(!root.join(REKEY_DIR).exists());
+        assert!(!finish_rekey(root).unwrap());
+    }
+
+    #[test]
+    fn verifies_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let asset = |name: &str, data: &[u8]| {
+            let path = dir.path().join(name);
+            write(&path, data).unwrap();
+            path
+        };
+
+        let encrypted = asset(
+            "Encrypted.rpgmvp",
+            &decrypter(OLD_KEY).encrypt(&png()).unwrap(),
+        );
+        let plain = asset("Plain.rpgmvp", &png());
+        let short = asset("Short.rpgmvp", &ENCRYPTED_HEADER[..]);
+        let garbage = asset("Garbage.rpgmvp", &[0; 64]);
+
+        let mut old_key = decrypter(OLD_KEY);
+        let mut new_key = decrypter(NEW_KEY);
+        let mut verify = |path| verify_asset(path, &mut old_key).unwrap();
+
+        assert!(verify(&encrypted).is_none());
+        assert!(verify(&plain) == Some(AssetProblem::Unencrypted));
+        assert!(verify(&short) == Some(AssetProblem::Corrupt));
+        assert!(verify(&garbage) == Some(AssetProblem::Corrupt));
+
+        assert!(
+            verify_asset(&encrypted, &mut new_key).unwrap()
+                == Some(AssetProblem::KeyMismatch)
+        );
+    }
+}
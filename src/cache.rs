@@ -0,0 +1,290 @@
+use crate::data_files::*;
+use anyhow::Result;
+use rvpacker_lib::types::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string_pretty};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    fs::{read, read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// File in translation directory, that stores hashes of the inputs of the
+/// previous `write`.
+pub const WRITE_CACHE_FILE: &str = ".rvpacker-write-cache";
+
+/// File categories, named the same way as in `--disable-processing` argument.
+pub const CATEGORIES: [(FileFlags, &str); 4] = [
+    (FileFlags::Map, "maps"),
+    (FileFlags::Other, "other"),
+    (FileFlags::System, "system"),
+    (FileFlags::Scripts, "scripts"),
+];
+
+/// Data file, written by the previous write.
+#[derive(Serialize, Deserialize)]
+struct WrittenFile {
+    path: PathBuf,
+    hash: String,
+}
+
+impl WrittenFile {
+    fn new(path: PathBuf) -> Result<Self> {
+        let hash = hash_file(&path)?;
+        Ok(Self { path, hash })
+    }
+
+    /// Whether the file is still the same, as it was written.
+    fn is_intact(&self) -> bool {
+        hash_file(&self.path).is_ok_and(|hash| hash == self.hash)
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    Ok(format!("{:016x}", xxh3_64(&read(path)?)))
+}
+
+/// Hashes of the inputs and the outputs of every data file.
+///
+/// A data file is rewritten, if its source file, or the translation file it's
+/// written from changed, or if its written file was removed or changed. Every
+/// map is written from `maps.txt`, so a change in it rewrites every map.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteCache {
+    /// Hash of the options, that affect the written files. If they change,
+    /// every file is rewritten.
+    options: String,
+    files: BTreeMap<String, String>,
+    /// Data files, that produced written files. Unused maps aren't written.
+    outputs: BTreeMap<String, WrittenFile>,
+}
+
+impl WriteCache {
+    /// Computes hashes of the current inputs.
+    ///
+    /// `options` is a string representation of every option, that affects the
+    /// written files.
+    pub fn new(
+        options: &str,
+        source_hashes: &BTreeMap<String, String>,
+        translation_path: &Path,
+    ) -> Result<Self> {
+        let mut translation_hashes = HashMap::new();
+
+        for entry in read_dir(translation_path)?.flatten() {
+            let path = entry.path();
+
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                translation_hashes.insert(
+                    entry.file_name().to_string_lossy().into_owned(),
+                    xxh3_64(&read(&path)?),
+                );
+            }
+        }
+
+        // Maps are written along with `MapInfos`, which marks unused maps.
+        let mapinfos_hash = source_hashes
+            .iter()
+            .find(|(file, _)| file.to_lowercase().starts_with("mapinfos."))
+            .map(|(_, hash)| hash.as_str())
+            .unwrap_or_default();
+
+        let mut files = BTreeMap::new();
+
+        for (file, hash) in source_hashes {
+            let Some(category) = get_data_file_category(file) else {
+                continue;
+            };
+
+            let translation_file = get_translation_file(file);
+            let mut inputs = format!(
+                "{file}: {hash}\ntranslation/{translation_file}: {:016x}\n",
+                translation_hashes
+                    .get(&translation_file)
+                    .copied()
+                    .unwrap_or_default()
+            );
+
+            if category == FileFlags::Map {
+                let _ = writeln!(inputs, "mapinfos: {mapinfos_hash}");
+            }
+
+            files.insert(
+                file.clone(),
+                format!("{:016x}", xxh3_64(inputs.as_bytes())),
+            );
+        }
+
+        Ok(Self {
+            options: format!("{:016x}", xxh3_64(options.as_bytes())),
+            files,
+            outputs: BTreeMap::new(),
+        })
+    }
+
+    /// Loads the cache of the previous write.
+    ///
+    /// Cache is disposable, so if it's missing or malformed, empty cache is
+    /// returned, and everything is rewritten.
+    pub fn load(path: &Path) -> Self {
+        read_to_string(path)
+            .ok()
+            .and_then(|content| from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write(path, to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns data files from `files`, which inputs in `current` differ from
+    /// this cache, or which written files are missing or changed.
+    pub fn get_outdated(
+        &self,
+        current: &Self,
+        files: &[String],
+    ) -> Vec<String> {
+        if self.options != current.options {
+            return files.to_vec();
+        }
+
+        files
+            .iter()
+            .filter(|file| {
+                self.files.get(*file) != current.files.get(*file)
+                    || self
+                        .outputs
+                        .get(*file)
+                        .is_some_and(|output| !output.is_intact())
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Stores hashes of `files`, that were written with the `current` inputs
+    /// to `written` paths, and forgets the files, that no longer exist.
+    pub fn update(
+        &mut self,
+        current: &Self,
+        files: &[String],
+        written: &[PathBuf],
+    ) -> Result<()> {
+        if self.options != current.options {
+            self.options.clone_from(&current.options);
+            self.files.clear();
+            self.outputs.clear();
+        }
+
+        self.files
+            .retain(|file, _| current.files.contains_key(file));
+        self.outputs
+            .retain(|file, _| current.files.contains_key(file));
+
+        for file in files {
+            if let Some(hash) = current.files.get(file) {
+                self.files.insert(file.clone(), hash.clone());
+            }
+
+            self.outputs.remove(file);
+        }
+
+        for path in written {
+            let Some(file) = path.file_name().and_then(|name| name.to_str())
+            else {
+                continue;
+            };
+
+            if files.iter().any(|written_file| written_file == file) {
+                self.outputs
+                    .insert(file.to_owned(), WrittenFile::new(path.clone())?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::remove_file;
+
+    fn source_hashes(items_hash: &str) -> BTreeMap<String, String> {
+        [
+            ("Actors.json", "1"),
+            ("Items.json", items_hash),
+            ("Map001.json", "3"),
+            ("MapInfos.json", "4"),
+        ]
+        .into_iter()
+        .map(|(file, hash)| (file.into(), hash.into()))
+        .collect()
+    }
+
+    #[test]
+    fn rewrites_only_changed_files() {
+        let translation = tempfile::tempdir().unwrap();
+        let translation_path = translation.path();
+        write(translation_path.join("actors.txt"), "a<#>b").unwrap();
+        write(translation_path.join("items.txt"), "c<#>d").unwrap();
+        write(translation_path.join("maps.txt"), "e<#>f").unwrap();
+
+        let files: Vec<String> = ["Map001.json", "Actors.json", "Items.json"]
+            .map(Into::into)
+            .into();
+        let mut cache = WriteCache::default();
+        let current =
+            WriteCache::new("", &source_hashes("2"), translation_path).unwrap();
+        assert_eq!(cache.get_outdated(&current, &files), files);
+        cache.update(&current, &files, &[]).unwrap();
+
+        write(translation_path.join("actors.txt"), "a<#>g").unwrap();
+        let current =
+            WriteCache::new("", &source_hashes("2"), translation_path).unwrap();
+        assert_eq!(cache.get_outdated(&current, &files), ["Actors.json"]);
+        cache.update(&current, &files, &[]).unwrap();
+
+        let current =
+            WriteCache::new("", &source_hashes("5"), translation_path).unwrap();
+        assert_eq!(cache.get_outdated(&current, &files), ["Items.json"]);
+
+        let current =
+            WriteCache::new("x", &source_hashes("2"), translation_path)
+                .unwrap();
+        assert_eq!(cache.get_outdated(&current, &files), files);
+    }
+
+    #[test]
+    fn rewrites_removed_and_changed_outputs() {
+        let translation = tempfile::tempdir().unwrap();
+        let translation_path = translation.path();
+        let output = tempfile::tempdir().unwrap();
+        let actors = output.path().join("Actors.json");
+        let items = output.path().join("Items.json");
+        write(translation_path.join("items.txt"), "c<#>d").unwrap();
+        write(&actors, "[]").unwrap();
+        write(&items, "[]").unwrap();
+
+        let files: Vec<String> =
+            ["Actors.json", "Items.json"].map(Into::into).into();
+        let current =
+            WriteCache::new("", &source_hashes("2"), translation_path).unwrap();
+        let mut cache = WriteCache::default();
+        cache
+            .update(&current, &files, &[actors.clone(), items.clone()])
+            .unwrap();
+        assert!(cache.get_outdated(&current, &files).is_empty());
+
+        remove_file(&actors).unwrap();
+        write(&items, "[null]").unwrap();
+        assert_eq!(cache.get_outdated(&current, &files), files);
+
+        write(&actors, "[]").unwrap();
+        cache.update(&current, &files, &[actors]).unwrap();
+        assert!(cache.get_outdated(&current, &files).is_empty());
+    }
+}
@@ -0,0 +1,702 @@
+use crate::{localization::*, metadata::Metadata, output::*};
+use anyhow::{Result, bail};
+use clap::{ArgMatches, parser::ValueSource};
+use rvpacker_lib::types::*;
+use serde::Deserialize;
+use std::{
+    fmt::Debug,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+pub const CONFIG_FILE_NAME: &str = "rvpacker.toml";
+
+pub const READ_MODES: [&str; 3] = ["default", "append", "force"];
+pub const DUPLICATE_MODES: [&str; 2] = ["allow", "remove"];
+pub const GAME_TYPES: [&str; 4] = ["auto", "termina", "lisa", "none"];
+pub const PROCESSING_FILES: [&str; 5] =
+    ["maps", "other", "system", "plugins", "scripts"];
+pub const ENGINES: [&str; 2] = ["mv", "mz"];
+pub const LANGUAGES: [&str; 2] = ["en", "ru"];
+
+/// Project configuration, that sets the defaults for command line options.
+///
+/// Every key corresponds to the long name of the option it replaces. Options,
+/// that name a single file or value for one run (`--file`, `--archive`,
+/// `--glob`, `--new-key`), have no keys, since a project-wide default for them
+/// would silently apply to every run.
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub input_dir: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub language: Option<String>,
+    pub progress: Option<bool>,
+    pub verbose: Option<bool>,
+    pub fail_fast: Option<bool>,
+    pub output_format: Option<String>,
+    pub read_mode: Option<String>,
+    pub romanize: Option<bool>,
+    pub trim: Option<bool>,
+    pub ignore: Option<bool>,
+    pub create_ignore: Option<bool>,
+    pub disable_custom_processing: Option<bool>,
+    pub disable_processing: Option<Vec<String>>,
+    pub duplicate_mode: Option<String>,
+    pub game_type: Option<String>,
+    pub profiles_dir: Option<PathBuf>,
+    pub key: Option<String>,
+    pub engine: Option<String>,
+}
+
+impl Config {
+    /// Returns the explicitly passed config path, or `rvpacker.toml` in the
+    /// input directory, if it exists.
+    pub fn find(
+        explicit: Option<PathBuf>,
+        input_dir: &Path,
+    ) -> Option<PathBuf> {
+        explicit.or_else(|| {
+            let path = input_dir.join(CONFIG_FILE_NAME);
+            path.exists().then_some(path)
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut config: Self = toml::from_str(&read_to_string(path)?)?;
+
+        // Paths in config are relative to the config itself.
+        let config_dir = path.parent().unwrap_or(Path::new(""));
+
+        for dir in [
+            &mut config.input_dir,
+            &mut config.output_dir,
+            &mut config.profiles_dir,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *dir = config_dir.join(&dir);
+        }
+
+        Ok(config)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Source {
+    Default,
+    ConfigFile,
+    CommandLine,
+    Metadata,
+}
+
+impl Source {
+    pub fn name(self, localization: &Localization) -> &'static str {
+        match self {
+            Self::Default => localization.default_source,
+            Self::ConfigFile => localization.config_file_source,
+            Self::CommandLine => localization.command_line_source,
+            Self::Metadata => localization.metadata_source,
+        }
+    }
+}
+
+/// Resolved value of a single option, along with where it came from.
+///
+/// Precedence, from lowest to highest: default value, config file, command
+/// line, and `.rvpacker-metadata`, because translation files must be
+/// processed with the same options they were read with.
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    pub fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    fn resolve(cli: Option<T>, config: Option<T>, default: T) -> Self {
+        if let Some(value) = cli {
+            Self::new(value, Source::CommandLine)
+        } else if let Some(value) = config {
+            Self::new(value, Source::ConfigFile)
+        } else {
+            Self::new(default, Source::Default)
+        }
+    }
+
+    pub fn set(&mut self, value: T, source: Source) {
+        self.value = value;
+        self.source = source;
+    }
+}
+
+pub struct Settings {
+    pub language: Setting<Language>,
+    pub input_dir: Setting<PathBuf>,
+    pub output_dir: Setting<Option<PathBuf>>,
+    pub progress: Setting<bool>,
+    pub verbose: Setting<bool>,
+    pub fail_fast: Setting<bool>,
+    pub output_format: Setting<OutputFormat>,
+    pub read_mode: Setting<ReadMode>,
+    pub romanize: Setting<bool>,
+    pub trim: Setting<bool>,
+    pub ignore: Setting<bool>,
+    pub create_ignore: Setting<bool>,
+    pub disable_custom_processing: Setting<bool>,
+    pub file_flags: Setting<FileFlags>,
+    pub duplicate_mode: Setting<DuplicateMode>,
+    /// `None` means that game type is detected from the game title.
+    pub game_type: Setting<Option<GameType>>,
+    /// Directory with game profiles, that are checked before the bundled
+    /// ones.
+    pub profiles_dir: Setting<Option<PathBuf>>,
+    pub key: Setting<Option<String>>,
+    pub engine: Setting<Option<String>>,
+}
+
+/// Returns the value of the argument, only if it was explicitly passed in
+/// the command line.
+///
+/// Arguments are looked up in every passed subcommand level, since not every
+/// subcommand defines every argument.
+fn cli_value<T: Clone + Send + Sync + 'static>(
+    matches: &[&ArgMatches],
+    id: &str,
+) -> Option<T> {
+    matches
+        .iter()
+        .find_map(|matches| match matches.try_get_one::<T>(id) {
+            Ok(Some(value))
+                if matches.value_source(id)
+                    == Some(ValueSource::CommandLine) =>
+            {
+                Some(value.clone())
+            }
+            _ => None,
+        })
+}
+
+fn cli_values(matches: &[&ArgMatches], id: &str) -> Option<Vec<String>> {
+    matches.iter().find_map(|matches| {
+        match matches.try_get_many::<String>(id) {
+            Ok(Some(values))
+                if matches.value_source(id)
+                    == Some(ValueSource::CommandLine) =>
+            {
+                Some(values.cloned().collect())
+            }
+            _ => None,
+        }
+    })
+}
+
+fn validate<'a>(
+    key: &str,
+    value: Option<&'a String>,
+    allowed: &[&str],
+    localization: &Localization,
+) -> Result<Option<&'a str>> {
+    match value {
+        Some(value) if !allowed.contains(&value.as_str()) => bail!(
+            "{key} = {value:?}: {msg} {allowed}",
+            msg = localization.config_invalid_value_msg,
+            allowed = allowed.join(", ")
+        ),
+        _ => Ok(value.map(String::as_str)),
+    }
+}
+
+pub fn parse_read_mode(mode: &str) -> ReadMode {
+    match mode {
+        "default" => ReadMode::Default,
+        "append" => ReadMode::Append,
+        "force" => ReadMode::Force,
+        _ => unreachable!(),
+    }
+}
+
+pub fn parse_duplicate_mode(mode: &str) -> DuplicateMode {
+    match mode {
+        "allow" => DuplicateMode::Allow,
+        "remove" => DuplicateMode::Remove,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns `None` for `auto`.
+pub fn parse_game_type(game_type: &str) -> Option<GameType> {
+    match game_type {
+        "auto" => None,
+        "termina" => Some(GameType::Termina),
+        "lisa" => Some(GameType::LisaRPG),
+        "none" => Some(GameType::None),
+        _ => unreachable!(),
+    }
+}
+
+pub const fn game_type_name(game_type: GameType) -> &'static str {
+    match game_type {
+        GameType::None => "none",
+        GameType::Termina => "termina",
+        GameType::LisaRPG => "lisa",
+    }
+}
+
+pub fn parse_file_flags<S: AsRef<str>>(disable_processing: &[S]) -> FileFlags {
+    let mut file_flags = FileFlags::all();
+
+    for disable_processing_of in disable_processing {
+        file_flags.remove(match disable_processing_of.as_ref() {
+            "maps" => FileFlags::Map,
+            "other" => FileFlags::Other,
+            "system" => FileFlags::System,
+            "plugins" | "scripts" => FileFlags::Scripts,
+            _ => unreachable!(),
+        })
+    }
+
+    file_flags
+}
+
+impl Settings {
+    pub fn resolve(
+        language: Setting<Language>,
+        matches: &[&ArgMatches],
+        config: Option<&Config>,
+        localization: &Localization,
+    ) -> Result<Self> {
+        let default_config = Config::default();
+        let config = config.unwrap_or(&default_config);
+
+        let read_mode = validate(
+            "read-mode",
+            config.read_mode.as_ref(),
+            &READ_MODES,
+            localization,
+        )?;
+        let duplicate_mode = validate(
+            "duplicate-mode",
+            config.duplicate_mode.as_ref(),
+            &DUPLICATE_MODES,
+            localization,
+        )?;
+        let game_type = validate(
+            "game-type",
+            config.game_type.as_ref(),
+            &GAME_TYPES,
+            localization,
+        )?;
+        validate(
+            "language",
+            config.language.as_ref(),
+            &LANGUAGES,
+            localization,
+        )?;
+        let engine =
+            validate("engine", config.engine.as_ref(), &ENGINES, localization)?;
+        let output_format = validate(
+            "output-format",
+            config.output_format.as_ref(),
+            &OUTPUT_FORMATS,
+            localization,
+        )?;
+
+        for file in config.disable_processing.iter().flatten() {
+            validate(
+                "disable-processing",
+                Some(file),
+                &PROCESSING_FILES,
+                localization,
+            )?;
+        }
+
+        let flag = |id: &str, config: Option<bool>| {
+            Setting::resolve(
+                cli_value::<bool>(matches, id).filter(|&flag| flag),
+                config,
+                false,
+            )
+        };
+
+        Ok(Self {
+            language,
+            input_dir: Setting::resolve(
+                cli_value(matches, "input-dir"),
+                config.input_dir.clone(),
+                PathBuf::from("./"),
+            ),
+            output_dir: Setting::resolve(
+                cli_value(matches, "output-dir").map(Some),
+                config.output_dir.clone().map(Some),
+                None,
+            ),
+            progress: flag("progress", config.progress),
+            verbose: flag("verbose", config.verbose),
+            fail_fast: flag("fail-fast", config.fail_fast),
+            output_format: Setting::resolve(
+                cli_value::<String>(matches, "output-format")
+                    .map(|format| OutputFormat::from_name(&format)),
+                output_format.map(OutputFormat::from_name),
+                OutputFormat::Text,
+            ),
+            read_mode: Setting::resolve(
+                cli_value::<String>(matches, "read-mode")
+                    .map(|mode| parse_read_mode(&mode)),
+                read_mode.map(parse_read_mode),
+                ReadMode::Default,
+            ),
+            romanize: flag("romanize", config.romanize),
+            trim: flag("trim", config.trim),
+            ignore: flag("ignore", config.ignore),
+            create_ignore: flag("create-ignore", config.create_ignore),
+            disable_custom_processing: flag(
+                "disable-custom-processing",
+                config.disable_custom_processing,
+            ),
+            file_flags: Setting::resolve(
+                cli_values(matches, "disable-processing")
+                    .map(|files| parse_file_flags(&files)),
+                config
+                    .disable_processing
+                    .as_ref()
+                    .map(|files| parse_file_flags(files)),
+                FileFlags::all(),
+            ),
+            duplicate_mode: Setting::resolve(
+                cli_value::<String>(matches, "duplicate-mode")
+                    .map(|mode| parse_duplicate_mode(&mode)),
+                duplicate_mode.map(parse_duplicate_mode),
+                DuplicateMode::Allow,
+            ),
+            game_type: Setting::resolve(
+                cli_value::<String>(matches, "game-type")
+                    .map(|game_type| parse_game_type(&game_type)),
+                game_type.map(parse_game_type),
+                None,
+            ),
+            profiles_dir: Setting::resolve(
+                cli_value(matches, "profiles-dir").map(Some),
+                config.profiles_dir.clone().map(Some),
+                None,
+            ),
+            key: Setting::resolve(
+                cli_value(matches, "key").map(Some),
+                config.key.clone().map(Some),
+                None,
+            ),
+            engine: Setting::resolve(
+                cli_value(matches, "engine").map(Some),
+                engine.map(|engine| Some(engine.to_owned())),
+                None,
+            ),
+        })
+    }
+
+    /// Overrides the settings with the options, that translation files were
+    /// read with. File flags are only restricted to the read files, unless
+    /// reading in `append` mode, which reads the new files.
+    pub fn apply_metadata(
+        &mut self,
+        metadata: &Metadata,
+        append: bool,
+        localization: &Localization,
+    ) {
+        if metadata.romanize {
+            info!("{}", localization.enabling_romanize_metadata_msg);
+            self.romanize.set(true, Source::Metadata);
+        }
+
+        if metadata.disable_custom_processing {
+            if !self.disable_custom_processing.value {
+                info!(
+                    "{}",
+                    localization.disabling_custom_processing_metadata_msg
+                );
+            }
+
+            self.disable_custom_processing.set(true, Source::Metadata);
+        }
+
+        if let Some(game_type) = metadata.game_type {
+            self.game_type.set(Some(game_type), Source::Metadata);
+        }
+
+        if metadata.trim {
+            info!("{}", localization.enabling_trim_metadata_msg);
+            self.trim.set(true, Source::Metadata);
+        }
+
+        if metadata.duplicate_mode as u8 != self.duplicate_mode.value as u8 {
+            info!("{}", localization.setting_duplicate_mode_metadata_msg);
+        }
+
+        self.duplicate_mode
+            .set(metadata.duplicate_mode, Source::Metadata);
+
+        // Files, that weren't read, cannot be written or purged.
+        let file_flags = self.file_flags.value & metadata.file_flags;
+
+        if !append && file_flags != self.file_flags.value {
+            info!("{}", localization.restricting_file_flags_metadata_msg);
+            self.file_flags.set(file_flags, Source::Metadata);
+        }
+    }
+
+    /// Logs every resolved setting along with its source.
+    pub fn show(
+        &self,
+        config_path: Option<&Path>,
+        localization: &Localization,
+    ) {
+        fn line<T: Debug>(
+            key: &str,
+            value: T,
+            source: Source,
+            localization: &Localization,
+        ) {
+            info!("{key} = {value:?} ({})", source.name(localization));
+        }
+
+        match config_path {
+            Some(path) => {
+                info!("{}: {}", localization.config_file_source, path.display())
+            }
+            None => info!("{}", localization.config_file_not_found_msg),
+        }
+
+        let language = match self.language.value {
+            Language::English => "en",
+            Language::Russian => "ru",
+        };
+        let read_mode = match self.read_mode.value {
+            ReadMode::Append => "append",
+            ReadMode::Force => "force",
+            _ => "default",
+        };
+        let duplicate_mode = match self.duplicate_mode.value {
+            DuplicateMode::Allow => "allow",
+            DuplicateMode::Remove => "remove",
+        };
+        let game_type = self.game_type.value.map_or("auto", game_type_name);
+        let disable_processing: Vec<&str> = [
+            (FileFlags::Map, "maps"),
+            (FileFlags::Other, "other"),
+            (FileFlags::System, "system"),
+            (FileFlags::Scripts, "scripts"),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| !self.file_flags.value.contains(*flag))
+        .map(|(_, name)| name)
+        .collect();
+
+        line("language", language, self.language.source, localization);
+        line(
+            "input-dir",
+            &self.input_dir.value,
+            self.input_dir.source,
+            localization,
+        );
+        line(
+            "output-dir",
+            self.output_dir
+                .value
+                .as_ref()
+                .unwrap_or(&self.input_dir.value),
+            self.output_dir.source,
+            localization,
+        );
+        line(
+            "progress",
+            self.progress.value,
+            self.progress.source,
+            localization,
+        );
+        line(
+            "verbose",
+            self.verbose.value,
+            self.verbose.source,
+            localization,
+        );
+        line(
+            "fail-fast",
+            self.fail_fast.value,
+            self.fail_fast.source,
+            localization,
+        );
+        line(
+            "output-format",
+            self.output_format.value.name(),
+            self.output_format.source,
+            localization,
+        );
+        line("read-mode", read_mode, self.read_mode.source, localization);
+        line(
+            "romanize",
+            self.romanize.value,
+            self.romanize.source,
+            localization,
+        );
+        line("trim", self.trim.value, self.trim.source, localization);
+        line(
+            "ignore",
+            self.ignore.value,
+            self.ignore.source,
+            localization,
+        );
+        line(
+            "create-ignore",
+            self.create_ignore.value,
+            self.create_ignore.source,
+            localization,
+        );
+        line(
+            "disable-custom-processing",
+            self.disable_custom_processing.value,
+            self.disable_custom_processing.source,
+            localization,
+        );
+        line(
+            "disable-processing",
+            disable_processing,
+            self.file_flags.source,
+            localization,
+        );
+        line(
+            "duplicate-mode",
+            duplicate_mode,
+            self.duplicate_mode.source,
+            localization,
+        );
+        line("game-type", game_type, self.game_type.source, localization);
+        line(
+            "profiles-dir",
+            &self.profiles_dir.value,
+            self.profiles_dir.source,
+            localization,
+        );
+        line("key", &self.key.value, self.key.source, localization);
+        line(
+            "engine",
+            &self.engine.value,
+            self.engine.source,
+            localization,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction, Command};
+    use std::collections::BTreeMap;
+
+    const LOCALIZATION: Localization = Localization::new(Language::English);
+
+    fn matches(args: &[&str]) -> ArgMatches {
+        Command::new("rvpacker-txt-rs")
+            .args([
+                Arg::new("read-mode").long("read-mode"),
+                Arg::new("duplicate-mode").long("duplicate-mode"),
+                Arg::new("trim").long("trim").action(ArgAction::SetTrue),
+            ])
+            .get_matches_from(["rvpacker-txt-rs"].iter().chain(args).copied())
+    }
+
+    fn resolve(args: &[&str], config: &str) -> Result<Settings> {
+        Settings::resolve(
+            Setting::new(Language::English, Source::Default),
+            &[&matches(args)],
+            Some(&toml::from_str(config)?),
+            &LOCALIZATION,
+        )
+    }
+
+    fn metadata(duplicate_mode: DuplicateMode, trim: bool) -> Metadata {
+        Metadata {
+            version: 3,
+            tool_version: None,
+            engine_type: EngineType::New,
+            game_title: None,
+            game_type: None,
+            romanize: false,
+            disable_custom_processing: false,
+            trim,
+            duplicate_mode,
+            file_flags: FileFlags::all(),
+            source_hashes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn resolves_settings_by_precedence() {
+        let settings = resolve(&[], "").unwrap();
+        assert!(settings.read_mode.value == ReadMode::Default);
+        assert!(settings.read_mode.source == Source::Default);
+
+        let settings = resolve(&[], "read-mode = \"append\"").unwrap();
+        assert!(settings.read_mode.value == ReadMode::Append);
+        assert!(settings.read_mode.source == Source::ConfigFile);
+
+        let mut settings = resolve(
+            &[
+                "--read-mode",
+                "force",
+                "--duplicate-mode",
+                "remove",
+                "--trim",
+            ],
+            "read-mode = \"append\"\nduplicate-mode = \"allow\"",
+        )
+        .unwrap();
+        assert!(settings.read_mode.value == ReadMode::Force);
+        assert!(settings.read_mode.source == Source::CommandLine);
+        assert!(matches!(
+            settings.duplicate_mode.value,
+            DuplicateMode::Remove
+        ));
+        assert!(settings.duplicate_mode.source == Source::CommandLine);
+
+        settings.apply_metadata(
+            &metadata(DuplicateMode::Allow, false),
+            false,
+            &LOCALIZATION,
+        );
+        assert!(matches!(
+            settings.duplicate_mode.value,
+            DuplicateMode::Allow
+        ));
+        assert!(settings.duplicate_mode.source == Source::Metadata);
+
+        // Metadata only turns flags on, so the passed flag stays.
+        assert!(settings.trim.value);
+        assert!(settings.trim.source == Source::CommandLine);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("trimm = true").is_err());
+        assert!(toml::from_str::<Config>("file = \"Map001.json\"").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for config in [
+            "language = \"de\"",
+            "read-mode = \"overwrite\"",
+            "disable-processing = [\"maps\", \"map\"]",
+        ] {
+            let err = resolve(&[], config).err().unwrap().to_string();
+            assert!(
+                err.contains(LOCALIZATION.config_invalid_value_msg),
+                "{err}"
+            );
+        }
+
+        assert!(resolve(&[], "language = \"ru\"").is_ok());
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use rvpacker_lib::{get_engine_extension, types::*};
+use std::{
+    ffi::OsStr,
+    fs::{copy, create_dir_all, read_dir},
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// MV/MZ plugins file, which is read from `js` directory next to the source
+/// directory.
+pub const PLUGINS_FILE: &str = "plugins.js";
+
+/// First letters of the data files in `other` category, the same as writer
+/// recognizes them by.
+const OTHER_FILE_PREFIXES: [&str; 10] = [
+    "act", "arm", "cla", "com", "ene", "ite", "ski", "sta", "tro", "wea",
+];
+
+/// Returns the category of the data file, or `None` if writer doesn't write
+/// it.
+pub fn get_data_file_category(name: &str) -> Option<FileFlags> {
+    if name == PLUGINS_FILE {
+        return Some(FileFlags::Scripts);
+    }
+
+    let stem = Path::new(name).file_stem()?.to_str()?.to_lowercase();
+    let prefix = stem.get(..3).filter(|_| stem.len() > 3)?;
+
+    match prefix {
+        // `MapInfos` is read along with maps, but isn't written.
+        "map" if stem.as_bytes()[3].is_ascii_digit() => Some(FileFlags::Map),
+        "map" => None,
+        "sys" => Some(FileFlags::System),
+        "scr" => Some(FileFlags::Scripts),
+        _ if OTHER_FILE_PREFIXES.contains(&prefix) => Some(FileFlags::Other),
+        _ => None,
+    }
+}
+
+/// Returns the name of the translation file, which the data file is written
+/// from.
+pub fn get_translation_file(name: &str) -> String {
+    let category = get_data_file_category(name);
+
+    if name == PLUGINS_FILE {
+        String::from("plugins.txt")
+    } else if category == Some(FileFlags::Map) {
+        String::from("maps.txt")
+    } else if category == Some(FileFlags::System) {
+        String::from("system.txt")
+    } else if category == Some(FileFlags::Scripts) {
+        String::from("scripts.txt")
+    } else {
+        Path::new(name)
+            .with_extension("txt")
+            .to_string_lossy()
+            .to_lowercase()
+    }
+}
+
+/// Returns names of the data files of `file_flags` categories in
+/// `source_path`, ordered by category, and then by name.
+pub fn collect_data_files(
+    source_path: &Path,
+    engine_type: EngineType,
+    file_flags: FileFlags,
+) -> Result<Vec<String>> {
+    let extension = get_engine_extension(engine_type);
+    let mut files = Vec::new();
+
+    for entry in read_dir(source_path)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if Path::new(&name)
+            .extension()
+            .is_some_and(|ext| ext == extension)
+        {
+            files.push(name);
+        }
+    }
+
+    if engine_type.is_new()
+        && source_path.with_file_name("js").join(PLUGINS_FILE).exists()
+    {
+        files.push(PLUGINS_FILE.into());
+    }
+
+    let mut files: Vec<(u8, String)> = files
+        .into_iter()
+        .filter_map(|name| {
+            get_data_file_category(&name)
+                .filter(|category| file_flags.contains(*category))
+                .map(|category| (category.bits(), name))
+        })
+        .collect();
+
+    files.sort_unstable();
+    Ok(files.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Temporary source directory with copies of some of the data files.
+///
+/// Writer processes every file of a category in the source directory, so to
+/// write only some of them, it's pointed to this directory instead.
+pub struct SourceSubset {
+    dir: TempDir,
+    source_root: PathBuf,
+    source_path: PathBuf,
+}
+
+impl SourceSubset {
+    pub fn new(
+        source_path: &Path,
+        engine_type: EngineType,
+        files: &[String],
+    ) -> Result<Self> {
+        let dir = tempfile::tempdir()?;
+        let subset_path = dir
+            .path()
+            .join(source_path.file_name().unwrap_or(OsStr::new("data")));
+        create_dir_all(&subset_path)?;
+
+        for file in files {
+            if file == PLUGINS_FILE {
+                let js_path = dir.path().join("js");
+                create_dir_all(&js_path)?;
+                copy(
+                    source_path.with_file_name("js").join(PLUGINS_FILE),
+                    js_path.join(PLUGINS_FILE),
+                )?;
+                continue;
+            }
+
+            // Maps can't be written without `MapInfos`.
+            if get_data_file_category(file) == Some(FileFlags::Map) {
+                let mapinfos =
+                    format!("MapInfos.{}", get_engine_extension(engine_type));
+
+                if !subset_path.join(&mapinfos).exists() {
+                    copy(
+                        source_path.join(&mapinfos),
+                        subset_path.join(&mapinfos),
+                    )?;
+                }
+            }
+
+            copy(source_path.join(file), subset_path.join(file))?;
+        }
+
+        Ok(Self {
+            dir,
+            source_root: source_path
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf(),
+            source_path: subset_path,
+        })
+    }
+
+    pub fn source_path(&self) -> &Path {
+        &self.source_path
+    }
+
+    /// Replaces the path in the error with the path of the original file.
+    pub fn restore_path(&self, result: &mut Result<Outcome, Error>) {
+        let path = match result {
+            Err(Error::ReadDirFailed { path, .. }) => path,
+            Err(
+                Error::ReadFileFailed { file, .. }
+                | Error::LoadFailed { file, .. }
+                | Error::JSONParseFailed { file, .. },
+            ) => file,
+            _ => return,
+        };
+
+        if let Ok(relative) = path.strip_prefix(self.dir.path()) {
+            *path = self.source_root.join(relative);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{localization::*, output::*};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    fs::{copy, read, read_dir},
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Changed,
+    Unchanged,
+}
+
+impl ChangeKind {
+    fn message(self, loc: &Localization) -> &'static str {
+        match self {
+            Self::Created => loc.dry_run_created_msg,
+            Self::Changed => loc.dry_run_changed_msg,
+            Self::Unchanged => loc.dry_run_unchanged_msg,
+        }
+    }
+}
+
+/// Effect, that `write` or `purge` would have on a single file.
+#[derive(Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub file: String,
+    /// Count of lines, that would be removed from the translation file by
+    /// `purge`.
+    pub purged_lines: Option<usize>,
+}
+
+impl Report for Change {
+    const EVENT_TYPE: &str = "change";
+
+    fn message(&self, loc: &Localization) -> &'static str {
+        self.kind.message(loc)
+    }
+
+    fn log(&self, message: &str, loc: &Localization) {
+        match self.purged_lines {
+            Some(purged_lines) if purged_lines > 0 => info!(
+                "{}: {message} ({} {purged_lines})",
+                self.file, loc.dry_run_purged_lines_label
+            ),
+            _ => info!("{}: {message}", self.file),
+        }
+    }
+}
+
+/// Copies files from the top level of `from` directory to `to` directory.
+pub fn copy_files(from: &Path, to: &Path) -> Result<()> {
+    for entry in read_dir(from)?.flatten() {
+        if entry.file_type()?.is_file() {
+            copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects paths of every file in `path` recursively, relative to `root`.
+fn collect_files(
+    root: &Path,
+    path: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in read_dir(path)?.flatten() {
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(())
+}
+
+/// Compares every file, produced in `dry_path` directory, with the file, that
+/// it would replace.
+///
+/// `get_real_path` maps path relative to `dry_path` to the path of the real
+/// file. With `count_purged_lines`, the difference in line count of `.txt`
+/// files is reported as purged lines.
+pub fn compare_files(
+    dry_path: &Path,
+    get_real_path: impl Fn(&Path) -> PathBuf,
+    count_purged_lines: bool,
+) -> Result<Vec<Change>> {
+    let mut files = Vec::new();
+    collect_files(dry_path, dry_path, &mut files)?;
+    files.sort_unstable();
+
+    let mut changes = Vec::with_capacity(files.len());
+
+    for file in files {
+        let new_content = read(dry_path.join(&file))?;
+        let real_path = get_real_path(&file);
+        let old_content = read(&real_path).ok();
+
+        let kind = match &old_content {
+            None => ChangeKind::Created,
+            Some(old_content) if *old_content == new_content => {
+                ChangeKind::Unchanged
+            }
+            Some(_) => ChangeKind::Changed,
+        };
+
+        let purged_lines = (count_purged_lines
+            && file.extension().is_some_and(|ext| ext == "txt"))
+        .then(|| {
+            let count = |content: &[u8]| content.split(|&b| b == b'\n').count();
+            old_content
+                .as_deref()
+                .map_or(0, count)
+                .saturating_sub(count(&new_content))
+        });
+
+        changes.push(Change {
+            kind,
+            file: real_path.display().to_string(),
+            purged_lines,
+        });
+    }
+
+    Ok(changes)
+}
+
+/// Reports every change, and, in text mode, the totals.
+pub fn report_changes(
+    changes: &[Change],
+    loc: &Localization,
+    output_format: OutputFormat,
+) {
+    for change in changes {
+        change.report(loc, output_format);
+    }
+
+    if output_format.is_json() {
+        return;
+    }
+
+    let count =
+        |kind| changes.iter().filter(|change| change.kind == kind).count();
+    let purged_lines: usize = changes
+        .iter()
+        .filter_map(|change| change.purged_lines)
+        .sum();
+
+    info!(
+        "{}: {} {}, {} {}, {} {}, {} {purged_lines}",
+        loc.dry_run_msg,
+        loc.dry_run_created_label,
+        count(ChangeKind::Created),
+        loc.dry_run_changed_label,
+        count(ChangeKind::Changed),
+        loc.dry_run_unchanged_label,
+        count(ChangeKind::Unchanged),
+        loc.dry_run_purged_lines_label,
+    );
+}
@@ -0,0 +1,540 @@
+use crate::localization::*;
+use anyhow::Result;
+use rvpacker_lib::constants::{
+    COMMENT_PREFIX, COMMENT_SUFFIX, MAP_DISPLAY_NAME_COMMENT_PREFIX, NEW_LINE,
+    SEPARATOR,
+};
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_dir, read_to_string, write},
+    path::Path,
+};
+use tracing::{info, warn};
+
+/// Key, that uniquely identifies an entry in a translation file: context and
+/// source text.
+pub type EntryKey = (String, String);
+
+/// A translatable entry of a translation file, prepared for exchange with
+/// external translation tools.
+///
+/// `\#` line break markers in source and translation are replaced with real
+/// line breaks.
+pub struct Entry {
+    /// Location of the entry, built from the comments preceding it, e.g.
+    /// `maps|Map: 1|Map Name: Town|Event ID: 3|Event Name: EV003`.
+    ///
+    /// If the same source text appears several times in the same location,
+    /// ` #2`, ` #3` and so on are appended to the context.
+    pub context: String,
+    pub source: String,
+    pub translation: String,
+    /// 1-based line number in the translation file.
+    pub line: usize,
+}
+
+impl Entry {
+    pub fn key(&self) -> EntryKey {
+        (self.context.clone(), self.source.clone())
+    }
+}
+
+enum Line<'a> {
+    /// Line that is kept as is.
+    Other(&'a str),
+    /// Translatable line. `head` contains everything up to and including the
+    /// separator, and `tail` - the raw translation.
+    Entry {
+        head: &'a str,
+        tail: &'a str,
+        entry: Entry,
+    },
+}
+
+fn from_txt(text: &str) -> String {
+    text.replace(NEW_LINE, "\n")
+}
+
+fn to_txt(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', NEW_LINE)
+}
+
+fn parse_lines<'a>(name: &str, content: &'a str) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    let mut context: Vec<(&str, &str)> = Vec::new();
+    let mut occurrences: HashMap<EntryKey, usize> = HashMap::new();
+
+    let mut push_entry = |lines: &mut Vec<Line<'a>>,
+                          context: &[(&str, &str)],
+                          head: &'a str,
+                          source: &str,
+                          translation: &'a str,
+                          line: usize| {
+        let mut context_string = name.to_string();
+
+        for (label, value) in context {
+            context_string.push('|');
+            context_string.push_str(label);
+
+            if !value.is_empty() {
+                context_string.push_str(": ");
+                context_string.push_str(&from_txt(value));
+            }
+        }
+
+        let source = from_txt(source);
+        let count = occurrences
+            .entry((context_string.clone(), source.clone()))
+            .or_default();
+        *count += 1;
+
+        if *count > 1 {
+            context_string.push_str(&format!(" #{count}"));
+        }
+
+        lines.push(Line::Entry {
+            head,
+            tail: translation,
+            entry: Entry {
+                context: context_string,
+                source,
+                translation: from_txt(translation),
+                line,
+            },
+        });
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        let Some((source, translation)) = line.split_once(SEPARATOR) else {
+            lines.push(Line::Other(line));
+            continue;
+        };
+
+        let head = &line[..source.len() + SEPARATOR.len()];
+
+        if let Some(display_name) = source
+            .strip_prefix(MAP_DISPLAY_NAME_COMMENT_PREFIX)
+            .and_then(|rest| rest.strip_suffix(COMMENT_SUFFIX))
+        {
+            // The only comment, that has translation.
+            context.push(("Displayed Name", ""));
+            push_entry(
+                &mut lines,
+                &context,
+                head,
+                display_name,
+                translation,
+                index + 1,
+            );
+            context.pop();
+        } else if let Some(label) = source
+            .strip_prefix(COMMENT_PREFIX)
+            .and_then(|rest| rest.strip_suffix(COMMENT_SUFFIX))
+        {
+            // Comment of the same kind starts the new location, so it
+            // replaces the previous one with all the comments after it.
+            if let Some(position) =
+                context.iter().position(|(existing, _)| *existing == label)
+            {
+                context.truncate(position);
+            }
+
+            context.push((label, translation));
+            lines.push(Line::Other(line));
+        } else {
+            push_entry(
+                &mut lines,
+                &context,
+                head,
+                source,
+                translation,
+                index + 1,
+            );
+        }
+    }
+
+    lines
+}
+
+/// Returns every translatable entry of the translation file.
+///
+/// `name` is the name of the file without extension, used as the start of the
+/// context.
+pub fn parse_entries(name: &str, content: &str) -> Vec<Entry> {
+    parse_lines(name, content)
+        .into_iter()
+        .filter_map(|line| match line {
+            Line::Entry { entry, .. } => Some(entry),
+            Line::Other(_) => None,
+        })
+        .collect()
+}
+
+/// Replaces translations in the translation file with the ones from
+/// `translations`, removing every applied translation from the map.
+///
+/// Entries, that are absent in `translations`, are left untouched, and so are
+/// the line endings of the file. Translations, that contain `<#>` delimiter,
+/// would break the line, so they're refused. Returns the new content of the
+/// file, the count of applied translations and the keys of refused ones.
+pub fn apply_translations(
+    name: &str,
+    content: &str,
+    translations: &mut HashMap<EntryKey, String>,
+) -> (String, usize, Vec<EntryKey>) {
+    let mut output = String::with_capacity(content.len());
+    let mut applied = 0;
+    let mut refused = Vec::new();
+
+    for (line, raw_line) in parse_lines(name, content)
+        .into_iter()
+        .zip(content.split_inclusive('\n'))
+    {
+        match line {
+            Line::Other(line) => output.push_str(line),
+            Line::Entry { head, tail, entry } => {
+                output.push_str(head);
+
+                match translations.remove(&entry.key()) {
+                    Some(translation) if translation.contains(SEPARATOR) => {
+                        output.push_str(tail);
+                        refused.push(entry.key());
+                    }
+                    Some(translation) => {
+                        output.push_str(&to_txt(&translation));
+                        applied += 1;
+                    }
+                    None => output.push_str(tail),
+                }
+            }
+        }
+
+        if raw_line.ends_with("\r\n") {
+            output.push_str("\r\n");
+        } else if raw_line.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+
+    (output, applied, refused)
+}
+
+/// Applies `translations`, parsed from `input_file_path`, to the translation
+/// file, and reports the ones, that weren't applied.
+pub fn import_translations(
+    name: &str,
+    translation_file_path: &Path,
+    input_file_path: &Path,
+    content: &str,
+    mut translations: HashMap<EntryKey, String>,
+    localization: &Localization,
+) -> Result<()> {
+    let (content, applied, refused) =
+        apply_translations(name, content, &mut translations);
+    write(translation_file_path, content)?;
+
+    for (context, source) in refused {
+        warn!(
+            "{}: {context}: {source:?}: {}",
+            input_file_path.display(),
+            localization.exchange_separator_in_translation_msg
+        );
+    }
+
+    for ((context, source), _) in translations {
+        warn!(
+            "{}: {context}: {source:?}: {}",
+            input_file_path.display(),
+            localization.exchange_entry_not_found_msg
+        );
+    }
+
+    info!(
+        "{}: {} {applied}",
+        translation_file_path.display(),
+        localization.imported_file_msg
+    );
+
+    Ok(())
+}
+
+/// Format of the files, that translations are exchanged with external tools
+/// in.
+pub trait Format {
+    const EXTENSION: &str;
+
+    /// Serializes entries of the translation file named `name`.
+    fn export(name: &str, entries: &[Entry]) -> String;
+
+    /// Returns translations from the file, keyed by their context and source
+    /// text.
+    fn parse(
+        content: &str,
+        localization: &Localization,
+    ) -> Result<Vec<(EntryKey, String)>>;
+}
+
+/// Returns names of the files with `extension` in the directory, without
+/// extension, sorted.
+pub fn get_file_names(path: &Path, extension: &str) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+
+    for entry in read_dir(path)?.flatten() {
+        let path = entry.path();
+
+        if path.extension().is_some_and(|ext| ext == extension) {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    names.sort_unstable();
+    Ok(names)
+}
+
+/// Exports every translation file to `output_path` in format `F`.
+pub fn export<F: Format>(
+    translation_path: &Path,
+    output_path: &Path,
+    localization: &Localization,
+) -> Result<()> {
+    create_dir_all(output_path)?;
+
+    for name in get_file_names(translation_path, "txt")? {
+        let content =
+            read_to_string(translation_path.join(format!("{name}.txt")))?;
+        let output_file_path =
+            output_path.join(format!("{name}.{}", F::EXTENSION));
+
+        write(
+            &output_file_path,
+            F::export(&name, &parse_entries(&name, &content)),
+        )?;
+        info!(
+            "{}: {}",
+            output_file_path.display(),
+            localization.exported_file_msg
+        );
+    }
+
+    Ok(())
+}
+
+/// Imports translations from every file in format `F` in `input_path` to the
+/// corresponding translation files.
+///
+/// Translation files serve as a template, so they must exist.
+pub fn import<F: Format>(
+    input_path: &Path,
+    translation_path: &Path,
+    localization: &Localization,
+) -> Result<()> {
+    if !input_path.exists() {
+        anyhow::bail!(
+            "{}: {}",
+            input_path.display(),
+            localization.exchange_dir_missing_msg
+        );
+    }
+
+    for name in get_file_names(input_path, F::EXTENSION)? {
+        let input_file_path =
+            input_path.join(format!("{name}.{}", F::EXTENSION));
+        let translation_file_path =
+            translation_path.join(format!("{name}.txt"));
+
+        let Ok(content) = read_to_string(&translation_file_path) else {
+            warn!(
+                "{}: {}",
+                translation_file_path.display(),
+                localization.exchange_translation_file_missing_msg
+            );
+            continue;
+        };
+
+        let translations =
+            F::parse(&read_to_string(&input_file_path)?, localization)
+                .map_err(|err| {
+                    err.context(format!("{}", input_file_path.display()))
+                })?
+                .into_iter()
+                .collect();
+
+        import_translations(
+            &name,
+            &translation_file_path,
+            &input_file_path,
+            &content,
+            translations,
+            localization,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Translation file with every kind of line, that must survive the
+    /// exchange: comments, `\#` line breaks, quotes, delimiters of CSV and
+    /// TSV, and the same source text twice in the same location.
+    pub const TRANSLATION: &str = "<!-- Map --><#>1\r
+<!-- Map Name --><#>Town\r
+<!-- In-game Displayed Name: Town \"Big\" --><#>Город\r
+<!-- Event ID --><#>1\r
+Hello\\#world<#>Привет\\#мир\r
+Say \"hi\", then\\#leave<#>Скажи \"привет\",\\#уходи\r
+Tab\there; back\\slash<#>Таб\tздесь; обратный\\слэш\r
+Hello\\#world<#>Привет снова\r
+<!-- Event ID --><#>2\r
+Hello\\#world<#>\r
+";
+
+    /// Returns the translation file with every translation removed.
+    pub fn untranslated(content: &str) -> String {
+        content
+            .split_inclusive('\n')
+            .map(|line| match line.split_once(SEPARATOR) {
+                Some((source, _)) if !source.starts_with(COMMENT_PREFIX) => {
+                    format!("{source}{SEPARATOR}\r\n")
+                }
+                Some((source, _))
+                    if source.starts_with(MAP_DISPLAY_NAME_COMMENT_PREFIX) =>
+                {
+                    format!("{source}{SEPARATOR}\r\n")
+                }
+                _ => line.to_string(),
+            })
+            .collect()
+    }
+
+    /// Exports [`TRANSLATION`] in format `F`, and imports the result to the
+    /// same file without translations.
+    pub fn round_trip<F: Format>() -> String {
+        let localization = Localization::new(Language::English);
+        let exported = F::export("maps", &parse_entries("maps", TRANSLATION));
+        let mut translations = F::parse(&exported, &localization)
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        let (content, _, refused) = apply_translations(
+            "maps",
+            &untranslated(TRANSLATION),
+            &mut translations,
+        );
+
+        assert!(translations.is_empty());
+        assert!(refused.is_empty());
+        content
+    }
+
+    #[test]
+    fn parses_entries_with_context() {
+        let entries = parse_entries("maps", TRANSLATION);
+
+        assert_eq!(entries.len(), 6);
+        assert_eq!(
+            entries[0].context,
+            "maps|Map: 1|Map Name: Town|Displayed Name"
+        );
+        assert_eq!(entries[0].source, "Town \"Big\"");
+        assert_eq!(
+            entries[1].context,
+            "maps|Map: 1|Map Name: Town|Event ID: 1"
+        );
+        assert_eq!(entries[1].source, "Hello\nworld");
+        assert_eq!(entries[1].translation, "Привет\nмир");
+        assert_eq!(entries[1].line, 5);
+        assert_eq!(
+            entries[4].context,
+            "maps|Map: 1|Map Name: Town|Event ID: 1 #2"
+        );
+        assert_eq!(
+            entries[5].context,
+            "maps|Map: 1|Map Name: Town|Event ID: 2"
+        );
+    }
+
+    #[test]
+    fn applies_translations_losslessly() {
+        let mut translations = parse_entries("maps", TRANSLATION)
+            .into_iter()
+            .map(|entry| (entry.key(), entry.translation))
+            .collect();
+
+        let (content, applied, refused) = apply_translations(
+            "maps",
+            &untranslated(TRANSLATION),
+            &mut translations,
+        );
+
+        assert_eq!(content, TRANSLATION);
+        assert_eq!(applied, 6);
+        assert!(refused.is_empty());
+    }
+
+    #[test]
+    fn keeps_line_endings() {
+        let content = "Hello<#>\nWorld<#>\r\nBye<#>";
+        let mut translations = HashMap::from([
+            (
+                (String::from("maps"), String::from("Hello")),
+                "Привет".into(),
+            ),
+            ((String::from("maps"), String::from("Bye")), "Пока".into()),
+        ]);
+
+        let (content, applied, _) =
+            apply_translations("maps", content, &mut translations);
+
+        assert_eq!(content, "Hello<#>Привет\nWorld<#>\r\nBye<#>Пока");
+        assert_eq!(applied, 2);
+    }
+
+    #[test]
+    fn converts_line_breaks_in_translation() {
+        let mut translations = HashMap::from([(
+            (String::from("maps"), String::from("Hello")),
+            String::from("При\r\nвет\nмир"),
+        )]);
+
+        let (content, _, _) =
+            apply_translations("maps", "Hello<#>\n", &mut translations);
+
+        assert_eq!(content, "Hello<#>При\\#вет\\#мир\n");
+    }
+
+    #[test]
+    fn refuses_translation_with_delimiter() {
+        let mut translations = HashMap::from([(
+            (String::from("maps"), String::from("Hello")),
+            String::from("При<#>вет"),
+        )]);
+
+        let (content, applied, refused) =
+            apply_translations("maps", "Hello<#>Привет\n", &mut translations);
+
+        assert_eq!(content, "Hello<#>Привет\n");
+        assert_eq!(applied, 0);
+        assert_eq!(refused, [(String::from("maps"), String::from("Hello"))]);
+    }
+
+    #[test]
+    fn keeps_unmatched_translations() {
+        let mut translations = HashMap::from([(
+            (String::from("maps"), String::from("Goodbye")),
+            String::from("Пока"),
+        )]);
+
+        let (content, applied, _) =
+            apply_translations("maps", "Hello<#>\n", &mut translations);
+
+        assert_eq!(content, "Hello<#>\n");
+        assert_eq!(applied, 0);
+        assert_eq!(translations.len(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use color_print::cstr;
 
+#[derive(Clone, Copy)]
 pub enum Language {
     English,
     Russian,
@@ -16,15 +17,38 @@ pub struct Localization {
     pub read_command_desc: &'static str,
     pub write_command_desc: &'static str,
     pub purge_command_desc: &'static str,
+    pub stats_command_desc: &'static str,
+    pub validate_command_desc: &'static str,
 
     pub json_command_desc: &'static str,
     pub generate_json_command_desc: &'static str,
     pub write_json_command_desc: &'static str,
 
+    pub export_command_desc: &'static str,
+    pub import_command_desc: &'static str,
+    pub export_po_command_desc: &'static str,
+    pub import_po_command_desc: &'static str,
+    pub export_xliff_command_desc: &'static str,
+    pub import_xliff_command_desc: &'static str,
+    pub export_csv_command_desc: &'static str,
+    pub import_csv_command_desc: &'static str,
+    pub export_tsv_command_desc: &'static str,
+    pub import_tsv_command_desc: &'static str,
+
     pub asset_command_desc: &'static str,
     pub decrypt_command_desc: &'static str,
     pub encrypt_command_desc: &'static str,
     pub extract_key_command_desc: &'static str,
+    pub rekey_command_desc: &'static str,
+    pub verify_command_desc: &'static str,
+
+    pub archive_command_desc: &'static str,
+    pub list_archive_command_desc: &'static str,
+    pub extract_archive_command_desc: &'static str,
+    pub pack_archive_command_desc: &'static str,
+
+    pub config_command_desc: &'static str,
+    pub show_config_command_desc: &'static str,
 
     // Argument descriptions
     pub input_dir_arg_desc: &'static str,
@@ -39,6 +63,8 @@ pub struct Localization {
     pub romanize_desc: &'static str,
     pub disable_custom_processing_desc: &'static str,
     pub duplicate_mode_arg_desc: &'static str,
+    pub game_type_arg_desc: &'static str,
+    pub profiles_dir_arg_desc: &'static str,
     pub read_mode_arg_desc: &'static str,
     pub create_ignore_flag_desc: &'static str,
     pub ignore_flag_desc: &'static str,
@@ -47,6 +73,17 @@ pub struct Localization {
     pub key_arg_desc: &'static str,
     pub file_arg_desc: &'static str,
     pub engine_arg_desc: &'static str,
+    pub config_arg_desc: &'static str,
+    pub output_format_arg_desc: &'static str,
+    pub fail_fast_flag_desc: &'static str,
+    pub dry_run_flag_desc: &'static str,
+    pub full_flag_desc: &'static str,
+    pub watch_flag_desc: &'static str,
+    pub pack_archive_flag_desc: &'static str,
+    pub archive_arg_desc: &'static str,
+    pub glob_arg_desc: &'static str,
+    pub force_flag_desc: &'static str,
+    pub archive_version_arg_desc: &'static str,
 
     // Argument types
     pub mode_arg_type: &'static str,
@@ -57,23 +94,86 @@ pub struct Localization {
     pub key_arg_type: &'static str,
     pub file_arg_type: &'static str,
     pub engine_arg_type: &'static str,
+    pub glob_arg_type: &'static str,
+    pub config_path_arg_type: &'static str,
+    pub format_arg_type: &'static str,
 
     // Messages and warnings
     pub input_dir_missing: &'static str,
     pub output_dir_missing: &'static str,
     pub translation_dir_missing: &'static str,
     pub elapsed_time_msg: &'static str,
+    pub summary_msg: &'static str,
+    pub summary_read_label: &'static str,
+    pub summary_written_label: &'static str,
+    pub summary_purged_label: &'static str,
+    pub summary_skipped_label: &'static str,
+    pub summary_unused_label: &'static str,
+    pub summary_failed_label: &'static str,
+    pub fail_fast_stopped_msg: &'static str,
+    pub stats_total_label: &'static str,
+    pub stats_untranslated_label: &'static str,
+    pub stats_chars_label: &'static str,
+    pub stats_words_label: &'static str,
+    pub missing_delimiter_msg: &'static str,
+    pub duplicate_delimiter_msg: &'static str,
+    pub missing_source_file_msg: &'static str,
+    pub line_not_in_source_msg: &'static str,
+    pub control_code_mismatch_msg: &'static str,
+    pub line_break_mismatch_msg: &'static str,
+    pub leftover_source_script_msg: &'static str,
+    pub validation_passed_msg: &'static str,
+    pub validation_failed_msg: &'static str,
+    pub exported_file_msg: &'static str,
+    pub imported_file_msg: &'static str,
+    pub exchange_parse_failed_msg: &'static str,
+    pub exchange_dir_missing_msg: &'static str,
+    pub exchange_translation_file_missing_msg: &'static str,
+    pub exchange_entry_not_found_msg: &'static str,
+    pub exchange_separator_in_translation_msg: &'static str,
+    pub spreadsheet_column_missing_msg: &'static str,
+    pub dry_run_msg: &'static str,
+    pub dry_run_created_msg: &'static str,
+    pub dry_run_changed_msg: &'static str,
+    pub dry_run_unchanged_msg: &'static str,
+    pub dry_run_created_label: &'static str,
+    pub dry_run_changed_label: &'static str,
+    pub dry_run_unchanged_label: &'static str,
+    pub dry_run_purged_lines_label: &'static str,
+    pub file_up_to_date_msg: &'static str,
+    pub files_up_to_date_msg: &'static str,
+    pub watching_msg: &'static str,
+    pub rebuilt_msg: &'static str,
+    pub nothing_to_rebuild_msg: &'static str,
     pub custom_processing_enabled_msg: &'static str,
+    pub game_type_msg: &'static str,
+    pub game_type_disabled_reason: &'static str,
+    pub game_type_source_label: &'static str,
+    pub game_type_detected_reason: &'static str,
+    pub game_type_not_detected_reason: &'static str,
+    pub bundled_profile_label: &'static str,
     pub enabling_romanize_metadata_msg: &'static str,
     pub disabling_custom_processing_metadata_msg: &'static str,
     pub enabling_trim_metadata_msg: &'static str,
     pub setting_duplicate_mode_metadata_msg: &'static str,
+    pub restricting_file_flags_metadata_msg: &'static str,
+    pub metadata_load_failed_msg: &'static str,
+    pub metadata_not_object_msg: &'static str,
+    pub metadata_invalid_field_msg: &'static str,
+    pub metadata_missing_field_msg: &'static str,
+    pub metadata_unsupported_version_msg: &'static str,
+    pub metadata_migrated_msg: &'static str,
+    pub metadata_engine_mismatch_msg: &'static str,
+    pub source_file_changed_msg: &'static str,
     pub no_subcommand_specified_msg: &'static str,
     pub could_not_determine_game_engine_msg: &'static str,
+    pub www_layout_detected_msg: &'static str,
     pub game_ini_file_missing_msg: &'static str,
+    pub game_title_missing_msg: &'static str,
     pub ignore_file_does_not_exist_msg: &'static str,
     pub could_not_decrypt_ini_file_msg: &'static str,
-    pub engine_argument_required_msg: &'static str,
+    pub engine_undetected_msg: &'static str,
+    pub engine_detected_msg: &'static str,
     pub translation_already_exist_msg: &'static str,
     pub map_is_unused_msg: &'static str,
     pub generated_json_msg: &'static str,
@@ -95,8 +195,52 @@ pub struct Localization {
     pub write_file_failed_msg: &'static str,
 
     pub force_mode_warning: &'static str,
-    pub file_argument_missing_msg: &'static str,
     pub file_argument_is_not_file_msg: &'static str,
+    pub config_load_failed_msg: &'static str,
+    pub profiles_dir_missing_msg: &'static str,
+    pub profile_load_failed_msg: &'static str,
+    pub archive_missing_msg: &'static str,
+    pub archive_invalid_header_msg: &'static str,
+    pub archive_truncated_msg: &'static str,
+    pub archive_unsafe_entry_msg: &'static str,
+    pub archive_file_exists_msg: &'static str,
+    pub archive_extracted_file_msg: &'static str,
+    pub archive_entries_label: &'static str,
+    pub extracted_archive_msg: &'static str,
+    pub packed_archive_msg: &'static str,
+    pub archive_version_undetected_msg: &'static str,
+    pub invalid_glob_msg: &'static str,
+    pub file_too_large_msg: &'static str,
+    pub pack_archive_new_engine_msg: &'static str,
+    pub archive_not_packed_msg: &'static str,
+    pub invalid_key_msg: &'static str,
+    pub key_not_found_msg: &'static str,
+    pub key_from_system_json_msg: &'static str,
+    pub key_from_image_msg: &'static str,
+    pub decrypt_game_flag_desc: &'static str,
+    pub encrypt_game_flag_desc: &'static str,
+    pub key_generated_msg: &'static str,
+    pub remove_originals_flag_desc: &'static str,
+    pub game_file_conflict_msg: &'static str,
+    pub game_system_json_missing_msg: &'static str,
+    pub processed_assets_msg: &'static str,
+    pub new_key_arg_desc: &'static str,
+    pub asset_key_mismatch_msg: &'static str,
+    pub rekey_finished_msg: &'static str,
+    pub new_key_msg: &'static str,
+    pub asset_corrupt_msg: &'static str,
+    pub asset_wrong_key_msg: &'static str,
+    pub asset_unencrypted_msg: &'static str,
+    pub asset_verification_passed_msg: &'static str,
+    pub asset_verification_failed_msg: &'static str,
+    pub config_invalid_value_msg: &'static str,
+    pub config_file_not_found_msg: &'static str,
+
+    // Setting sources
+    pub default_source: &'static str,
+    pub config_file_source: &'static str,
+    pub command_line_source: &'static str,
+    pub metadata_source: &'static str,
 
     // Misc
     pub allowed_values: &'static str,
@@ -139,6 +283,12 @@ impl Localization {
             purge_command_desc: cstr!(
                 r#"<bold>Purges lines without translation from ".txt" translation files.</>"#
             ),
+            stats_command_desc: cstr!(
+                r#"<bold>Reports translation progress of ".txt" translation files.</>"#
+            ),
+            validate_command_desc: cstr!(
+                r#"<bold>Checks ".txt" translation files for errors against the source game files.</>"#
+            ),
 
             asset_command_desc: cstr!(
                 "<bold>Decrypt/encrypt RPG Maker MV/MZ audio and image assets."
@@ -156,7 +306,33 @@ impl Localization {
                                     .m4a => .rpgmvm/.m4a_"
             ),
             extract_key_command_desc: cstr!(
-                "<bold>Extracts key from the file, specified in --file argument."
+                "<bold>Extracts key from the file, specified in --file argument, or finds it in the game."
+            ),
+            rekey_command_desc: cstr!(
+                "<bold>Re-encrypts every encrypted asset of the game with a new key, and sets it in System.json."
+            ),
+            verify_command_desc: cstr!(
+                "<bold>Checks, that every encrypted asset decrypts with the key, and lists corrupt, mismatched and unencrypted assets."
+            ),
+
+            archive_command_desc: cstr!(
+                "<bold>Lists, extracts and packs RPG Maker XP/VX/VXAce .rgss archives."
+            ),
+            list_archive_command_desc: cstr!(
+                "<bold>Lists the files in the archive with their sizes."
+            ),
+            extract_archive_command_desc: cstr!(
+                "<bold>Extracts the files from the archive to the output directory."
+            ),
+            pack_archive_command_desc: cstr!(
+                "<bold>Packs the files from the input directory to the archive in the output directory."
+            ),
+
+            config_command_desc: cstr!(
+                "<bold>Provides the commands for project configuration file."
+            ),
+            show_config_command_desc: cstr!(
+                "<bold>Shows the final resolved settings and where each one came from."
             ),
 
             json_command_desc: cstr!(
@@ -169,6 +345,37 @@ impl Localization {
                 r#"<bold>Writes JSON representations of older engines' files from "json" directory back to original files.</>"#
             ),
 
+            export_command_desc: cstr!(
+                r#"<bold>Exports translation files to formats of external translation tools.</>"#
+            ),
+            import_command_desc: cstr!(
+                r#"<bold>Imports translations from formats of external translation tools back to translation files.</>"#
+            ),
+            export_po_command_desc: cstr!(
+                r#"<bold>Exports translation files to gettext PO catalogs in "po" directory.</>"#
+            ),
+            import_po_command_desc: cstr!(
+                r#"<bold>Imports translations from gettext PO catalogs in "po" directory.</>"#
+            ),
+            export_xliff_command_desc: cstr!(
+                r#"<bold>Exports translation files to XLIFF 2.0 document in "xliff" directory.</>"#
+            ),
+            import_xliff_command_desc: cstr!(
+                r#"<bold>Imports translations and segment states from XLIFF 2.0 document in "xliff" directory.</>"#
+            ),
+            export_csv_command_desc: cstr!(
+                r#"<bold>Exports translation files to CSV spreadsheets in "csv" directory.</>"#
+            ),
+            import_csv_command_desc: cstr!(
+                r#"<bold>Imports translations from CSV spreadsheets in "csv" directory.</>"#
+            ),
+            export_tsv_command_desc: cstr!(
+                r#"<bold>Exports translation files to TSV spreadsheets in "tsv" directory.</>"#
+            ),
+            import_tsv_command_desc: cstr!(
+                r#"<bold>Imports translations from TSV spreadsheets in "tsv" directory.</>"#
+            ),
+
             input_dir_arg_desc: r#"Input directory, containing game files."#,
             output_dir_arg_desc: r#"Output directory to output files to."#,
             progress_arg_desc: "Enables real-time progress logging.",
@@ -184,6 +391,8 @@ impl Localization {
             Right now, implemented for the following titles: LISA: The Painful and its derivatives, Fear & Hunger 2: Termina.\n\
             Will be automatically set if it was used in read.",
             duplicate_mode_arg_desc: "Controls how to handle duplicates in text.",
+            game_type_arg_desc: "Game, which text requires custom processing. With auto, it's detected by the game profiles.",
+            profiles_dir_arg_desc: "Directory with game profiles in .toml files. They're checked before the bundled profiles.",
             read_mode_arg_desc: "Defines how to read files.\n\
             default - If encounters existing translation files, aborts read.\n\
             append - Appends any new text from the game to the translation files, if the text is not already present. Unused lines are removed from translation files, and the lines order is sorted.\n\
@@ -191,9 +400,20 @@ impl Localization {
             trim_flag_desc: "Remove the leading and trailing whitespace from extracted strings. Don't use this option unless you know that trimming the text won't cause any incorrect behavior.",
             create_ignore_flag_desc: "Create an ignore file from purged lines, to prevent their further appearance when reading with append mode.",
             ignore_flag_desc: "Ignore entries from .rvpacker-ignore file. Use with append mode.",
-            key_arg_desc: "Encryption key for encrypt/decrypt operations.",
+            key_arg_desc: "Encryption key for encrypt/decrypt operations. decrypt finds it in the game, if it's not passed.",
             file_arg_desc: "File path (for single file processing or key extraction).",
-            engine_arg_desc: r#"Game engine ("mv" or "mz")."#,
+            engine_arg_desc: r#"Game engine ("mv" or "mz"). Detected from the game, if not passed."#,
+            config_arg_desc: "Path to the project configuration file. Options from it are used as defaults, which are overridden by command line arguments and metadata from previous read.",
+            output_format_arg_desc: "Output format of processing results. json prints one JSON event per line to stdout, while logs are printed to stderr.",
+            fail_fast_flag_desc: "Stop processing after the first error. `write` stops before the next file. `read` and `purge` process every file of a category (maps, other, system, scripts) together, so they stop after the category, in which the error occurred.",
+            dry_run_flag_desc: "Only report, which files would be created or changed and how many lines would be purged, without changing anything on disk.",
+            full_flag_desc: "Rewrite every file, even if its source and translation files haven't changed since the previous write.",
+            watch_flag_desc: "After writing, watch translation files, and rewrite the files, affected by every save. Runs until interrupted with Ctrl+C.",
+            pack_archive_flag_desc: "Write XP/VX/VXAce game files straight into the encrypted archive in the output directory, along with the other files of the original archive. Replaces the existing archive.",
+            archive_arg_desc: "Path to the archive. By default, Game.rgss3a, Game.rgss2a or Game.rgssad in the input directory is used, and packed archive is named by its version in the output directory.",
+            glob_arg_desc: "Process only the files, which paths match the glob pattern. Can be passed multiple times.",
+            force_flag_desc: "Overwrite existing files.",
+            archive_version_arg_desc: "Version of the packed archive. By default, it's detected from the extensions of the data files.",
 
             // Argument types
             mode_arg_type: "MODE",
@@ -204,6 +424,9 @@ impl Localization {
             key_arg_type: "KEY",
             file_arg_type: "INPUT_FILE",
             engine_arg_type: "ENGINE",
+            glob_arg_type: "PATTERN",
+            config_path_arg_type: "CONFIG_PATH",
+            format_arg_type: "FORMAT",
 
             // Messages and warnings
             input_dir_missing: "Input directory does not exist.",
@@ -211,19 +434,121 @@ impl Localization {
             translation_dir_missing: r#""translation" directory in the input directory does not exist."#,
 
             elapsed_time_msg: "Elapsed:",
+            summary_msg: "Summary",
+            summary_read_label: "read",
+            summary_written_label: "written",
+            summary_purged_label: "purged",
+            summary_skipped_label: "skipped",
+            summary_unused_label: "unused",
+            summary_failed_label: "failed",
+            fail_fast_stopped_msg: "An error occurred, stopping processing due to `--fail-fast`.",
+            stats_total_label: "Total",
+            stats_untranslated_label: "untranslated:",
+            stats_chars_label: "characters",
+            stats_words_label: "words",
+            missing_delimiter_msg: "Line is missing `<#>` delimiter.",
+            duplicate_delimiter_msg: "Line contains more than one `<#>` delimiter.",
+            missing_source_file_msg: "File doesn't exist in the source data.",
+            line_not_in_source_msg: "Line doesn't exist in the source data. It might have been edited, or the game was updated.",
+            control_code_mismatch_msg: "Control codes in translation don't match the source.",
+            line_break_mismatch_msg: r"Count of `\#` line breaks in translation doesn't match the source.",
+            leftover_source_script_msg: "Translation contains leftover characters of the source script.",
+            validation_passed_msg: "No problems found in translation files.",
+            validation_failed_msg: "Problems found in translation files:",
+            exported_file_msg: "Successfully exported file.",
+            imported_file_msg: "Imported translations:",
+            exchange_parse_failed_msg: "Couldn't parse the line.",
+            exchange_dir_missing_msg: "Directory with files to import does not exist. Use `export` command to create it.",
+            exchange_translation_file_missing_msg: "Translation file does not exist, so translations for it can't be imported.",
+            exchange_entry_not_found_msg: "Context or source text of the entry doesn't match the current translation file, so the entry is refused.",
+            exchange_separator_in_translation_msg: "Translation contains `<#>` delimiter, which can't be stored in a translation file, so the entry is refused.",
+            spreadsheet_column_missing_msg: "Required column is missing in the header row.",
+            dry_run_msg: "Dry run, nothing was changed on disk",
+            dry_run_created_msg: "File would be created.",
+            dry_run_changed_msg: "File would be changed.",
+            dry_run_unchanged_msg: "File would stay unchanged.",
+            dry_run_created_label: "created",
+            dry_run_changed_label: "changed",
+            dry_run_unchanged_label: "unchanged",
+            dry_run_purged_lines_label: "purged lines",
+            file_up_to_date_msg: "Source, translation and written files haven't changed since the previous write, so writing is skipped.",
+            files_up_to_date_msg: "files haven't changed since the previous write, so writing them is skipped. Use --full to rewrite them.",
+            watching_msg: "Watching for changes in translation files. Press Ctrl+C to stop.",
+            rebuilt_msg: "Rebuilt",
+            nothing_to_rebuild_msg: "Nothing to rebuild.",
             custom_processing_enabled_msg: "Custom processing for this game will be used. Use --disable-custom-processing to disable it.",
+            game_type_msg: "Game type",
+            game_type_disabled_reason: "Custom processing is disabled.",
+            game_type_source_label: "Source",
+            game_type_detected_reason: "Matched game profile",
+            game_type_not_detected_reason: "No game profile matches the game",
+            bundled_profile_label: "bundled",
             enabling_romanize_metadata_msg: "Enabling romanize according to the metadata from previous read.",
             disabling_custom_processing_metadata_msg: "Disabling custom processing according to the metadata from previous read.",
             enabling_trim_metadata_msg: "Enabling trimming according to the metadata from previous read.",
             setting_duplicate_mode_metadata_msg: "Setting duplicate mode according to the metadata from previous read.",
+            restricting_file_flags_metadata_msg: "Skipping files, that weren't processed in previous read, according to the metadata.",
+            metadata_load_failed_msg: "Loading metadata file failed.",
+            metadata_not_object_msg: "Metadata file must contain a JSON object.",
+            metadata_invalid_field_msg: "Metadata field has invalid value.",
+            metadata_missing_field_msg: "Required metadata field is missing.",
+            metadata_unsupported_version_msg: "Metadata file is created by a newer version of the tool. Metadata version:",
+            metadata_migrated_msg: "Migrated metadata file to the current version:",
+            metadata_engine_mismatch_msg: "Game engine differs from the one recorded in metadata from previous read. Make sure that input directory is correct.",
+            source_file_changed_msg: "Source file has changed or is missing since the previous read. Consider reading it again with `--mode append`.",
             no_subcommand_specified_msg: "No command was specified. Call rvpacker-txt-rs -h for help.",
             could_not_determine_game_engine_msg: "Couldn't determine game engine. Check the existence of System file inside data/original directory.",
+            www_layout_detected_msg: r#"Detected deployed MV game, using "www" directory as the game root."#,
             game_ini_file_missing_msg: "Game.ini file not found.",
+            game_title_missing_msg: "Couldn't find game title in System or Game.ini file.",
             ignore_file_does_not_exist_msg: ".rvpacker-ignore file does not exist. Aborting execution.",
             could_not_decrypt_ini_file_msg: "Couldn't decrypt Game.ini file. You can try to turn it UTF-8 yourself, after that everything will work.",
-            engine_argument_required_msg: "`--engine` argument is required.",
-            file_argument_missing_msg: "`--file` argument is missing. It's required in `extract_key` command.",
+            engine_undetected_msg: "Couldn't detect the engine of the game. Pass it with --engine.",
+            engine_detected_msg: "Engine is detected",
             file_argument_is_not_file_msg: "`--file` argument expects a file.",
+            config_load_failed_msg: "Loading configuration file failed.",
+            profiles_dir_missing_msg: "Couldn't read game profiles directory.",
+            profile_load_failed_msg: "Loading game profile failed.",
+            archive_missing_msg: "Couldn't find the archive in the input directory. Pass its path with --archive.",
+            archive_invalid_header_msg: "File is not an RGSS archive.",
+            archive_truncated_msg: "Archive is truncated or corrupted.",
+            archive_unsafe_entry_msg: "File path points outside of the output directory. Skipping.",
+            archive_file_exists_msg: "File already exists. Skipping. Use --force to overwrite it.",
+            archive_extracted_file_msg: "Extracted file.",
+            archive_entries_label: "files",
+            extracted_archive_msg: "Extracted files",
+            packed_archive_msg: "Packed files",
+            archive_version_undetected_msg: "Couldn't detect the archive version from the files. Pass it with --archive-version.",
+            invalid_glob_msg: "Invalid glob pattern.",
+            file_too_large_msg: "File is too large for the archive.",
+            pack_archive_new_engine_msg: "--pack-archive is only supported for XP, VX and VX Ace games.",
+            archive_not_packed_msg: "Archive wasn't packed, since some files failed to write.",
+            invalid_key_msg: "Encryption key must consist of 32 hexadecimal characters.",
+            key_not_found_msg: "Couldn't find the encryption key: System.json of the game has no encryptionKey, and there are no encrypted images to derive it from. Pass it with --key.",
+            key_from_system_json_msg: "Encryption key is read from",
+            key_from_image_msg: "Encryption key is derived from",
+            decrypt_game_flag_desc: "Decrypt every asset in img and audio directories of the game, and turn off asset encryption in its System.json, so the game runs with the plain assets.",
+            encrypt_game_flag_desc: "Encrypt every asset in img and audio directories of the game with its key, or a new one, and turn on asset encryption in its System.json.",
+            key_generated_msg: "Game has no encryption key, generated a new one",
+            remove_originals_flag_desc: "Remove the original files after processing, so only the results are left.",
+            game_file_conflict_msg: "--game processes the whole game and can't be used with --file.",
+            game_system_json_missing_msg: "data/System.json is not found. --game expects the game directory as the input directory.",
+            processed_assets_msg: "Processed assets",
+            new_key_arg_desc: "New encryption key. Generated randomly, if not passed.",
+            asset_key_mismatch_msg: "Asset doesn't decrypt with the key of the game, or is corrupted. Nothing was changed.",
+            rekey_finished_msg: "Finished re-keying, that was interrupted. Game already uses the new key.",
+            new_key_msg: "New encryption key",
+            asset_corrupt_msg: "File is not an encrypted asset, or is corrupted.",
+            asset_wrong_key_msg: "Asset doesn't decrypt with the key.",
+            asset_unencrypted_msg: "Asset isn't encrypted, but the game expects it to be.",
+            asset_verification_passed_msg: "Every asset decrypts with the key.",
+            asset_verification_failed_msg: "Problems found in assets:",
+            config_invalid_value_msg: "Invalid value. Allowed values:",
+            config_file_not_found_msg: "Configuration file is not found, using defaults.",
+            default_source: "default",
+            config_file_source: "config file",
+            command_line_source: "command line",
+            metadata_source: "metadata",
             read_file_msg: "Successfully read file.",
             written_file_msg: "Successfully written file.",
             purged_file_msg: "Successfully purged file.",
@@ -278,6 +603,12 @@ impl Localization {
             purge_command_desc: cstr!(
                 r#"<bold>Удаляет строки без перевода из текстовых файлов перевода.</>"#
             ),
+            stats_command_desc: cstr!(
+                r#"<bold>Выводит прогресс перевода текстовых файлов перевода.</>"#
+            ),
+            validate_command_desc: cstr!(
+                r#"<bold>Проверяет текстовые файлы перевода на ошибки, сравнивая их с исходными файлами игры.</>"#
+            ),
 
             asset_command_desc: cstr!(
                 "<bold>Расшифровывает/зашифровывает ассеты движков RPG Maker MV/MZ."
@@ -295,7 +626,33 @@ impl Localization {
             .m4a => .rpgmvm/.m4a_"
             ),
             extract_key_command_desc: cstr!(
-                "<bold>Достаёт ключ из файла, указанного в аргументе --file."
+                "<bold>Достаёт ключ из файла, указанного в аргументе --file, или находит его в игре."
+            ),
+            rekey_command_desc: cstr!(
+                "<bold>Перешифровывает все зашифрованные ассеты игры новым ключом, и устанавливает его в System.json."
+            ),
+            verify_command_desc: cstr!(
+                "<bold>Проверяет, что каждый зашифрованный ассет расшифровывается ключом, и выводит повреждённые, несовпадающие и незашифрованные ассеты."
+            ),
+
+            archive_command_desc: cstr!(
+                "<bold>Просматривает, распаковывает и упаковывает архивы .rgss RPG Maker XP/VX/VXAce."
+            ),
+            list_archive_command_desc: cstr!(
+                "<bold>Выводит файлы архива с их размерами."
+            ),
+            extract_archive_command_desc: cstr!(
+                "<bold>Распаковывает файлы архива в выходную директорию."
+            ),
+            pack_archive_command_desc: cstr!(
+                "<bold>Упаковывает файлы входной директории в архив в выходной директории."
+            ),
+
+            config_command_desc: cstr!(
+                "<bold>Предоставляет команды для работы с файлом конфигурации проекта."
+            ),
+            show_config_command_desc: cstr!(
+                "<bold>Показывает итоговые настройки и источник каждой из них."
             ),
 
             json_command_desc: cstr!(
@@ -308,6 +665,37 @@ impl Localization {
                 r#"<bold>Записывает JSON-репрезентации файлов старых движков из директории "json" обратно в исходные файлы."#
             ),
 
+            export_command_desc: cstr!(
+                r#"<bold>Экспортирует файлы перевода в форматы внешних инструментов перевода.</>"#
+            ),
+            import_command_desc: cstr!(
+                r#"<bold>Импортирует перевод из форматов внешних инструментов перевода обратно в файлы перевода.</>"#
+            ),
+            export_po_command_desc: cstr!(
+                r#"<bold>Экспортирует файлы перевода в каталоги gettext PO в директории "po".</>"#
+            ),
+            import_po_command_desc: cstr!(
+                r#"<bold>Импортирует перевод из каталогов gettext PO в директории "po".</>"#
+            ),
+            export_xliff_command_desc: cstr!(
+                r#"<bold>Экспортирует файлы перевода в документ XLIFF 2.0 в директории "xliff".</>"#
+            ),
+            import_xliff_command_desc: cstr!(
+                r#"<bold>Импортирует перевод и состояния сегментов из документа XLIFF 2.0 в директории "xliff".</>"#
+            ),
+            export_csv_command_desc: cstr!(
+                r#"<bold>Экспортирует файлы перевода в таблицы CSV в директории "csv".</>"#
+            ),
+            import_csv_command_desc: cstr!(
+                r#"<bold>Импортирует перевод из таблиц CSV в директории "csv".</>"#
+            ),
+            export_tsv_command_desc: cstr!(
+                r#"<bold>Экспортирует файлы перевода в таблицы TSV в директории "tsv".</>"#
+            ),
+            import_tsv_command_desc: cstr!(
+                r#"<bold>Импортирует перевод из таблиц TSV в директории "tsv".</>"#
+            ),
+
             input_dir_arg_desc: r#"Входная директория, содержащая файлы игры."#,
             output_dir_arg_desc: r#"Выходная директория, в которую будут помещены выходные файлы."#,
             progress_arg_desc: "Включает логирование в реальном времени.",
@@ -323,6 +711,8 @@ impl Localization {
             Сейчас, она имплементирована для следующих игр: LISA: The Painful и на её базе, Fear & Hunger 2: Termina.\n\
             Этот аргумент будет автоматически установлен, если был использован в чтении.",
             duplicate_mode_arg_desc: "Контролирует, что делать с дубликатами текста.",
+            game_type_arg_desc: "Игра, текст которой требует индивидуальной обработки. При значении auto она определяется по профилям игр.",
+            profiles_dir_arg_desc: "Директория с профилями игр в файлах .toml. Они проверяются перед встроенными профилями.",
             read_mode_arg_desc: "Определяет способ чтения файлов.\n\
             default - при обнаружении существующих файлов перевода прерывает чтение.\n\
             append - добавляет любой новый текст из игры к файлам перевода, если текст еще не присутствует. Неиспользуемые строки удаляются из файлов перевода, а порядок строк сортируется.\n\
@@ -335,9 +725,20 @@ impl Localization {
                 "Удалить лишние начальные и конечные пробелы из распарсенного текста. Убедитесь, что этот аргумент не удалит пробелы, которые задуманы для отображения в игре перед применением."
             ),
 
-            key_arg_desc: "Ключ шифрования для команд encrypt/decrypt.",
+            key_arg_desc: "Ключ шифрования для команд encrypt/decrypt. decrypt находит его в игре, если он не передан.",
             file_arg_desc: "Путь к файлу (для обработки одного файла или доставания ключа).",
-            engine_arg_desc: r#"Движок игры ("mv" или "mz")"#,
+            engine_arg_desc: r#"Движок игры ("mv" или "mz"). Определяется по игре, если не передан."#,
+            config_arg_desc: "Путь к файлу конфигурации проекта. Опции из него используются как значения по умолчанию, которые переопределяются аргументами командной строки и метаданными из прошлого чтения.",
+            output_format_arg_desc: "Формат вывода результатов обработки. json выводит по одному JSON событию на строку в stdout, а логи выводятся в stderr.",
+            fail_fast_flag_desc: "Остановить обработку после первой ошибки. `write` останавливается перед следующим файлом. `read` и `purge` обрабатывают все файлы категории (карты, другие, системный, скрипты) вместе, поэтому останавливаются после категории, в которой произошла ошибка.",
+            dry_run_flag_desc: "Только сообщить, какие файлы будут созданы или изменены и сколько строк будет удалено, ничего не изменяя на диске.",
+            full_flag_desc: "Записать все файлы, даже если их исходные файлы и файлы перевода не изменились с прошлой записи.",
+            watch_flag_desc: "После записи следить за файлами перевода, и перезаписывать файлы, затронутые каждым сохранением. Работает до прерывания с помощью Ctrl+C.",
+            pack_archive_flag_desc: "Записать файлы игры XP/VX/VXAce сразу в зашифрованный архив в выходной директории, вместе с остальными файлами исходного архива. Заменяет существующий архив.",
+            archive_arg_desc: "Путь к архиву. По умолчанию используется Game.rgss3a, Game.rgss2a или Game.rgssad во входной директории, а упакованный архив называется по его версии в выходной директории.",
+            glob_arg_desc: "Обрабатывать только файлы, пути которых соответствуют glob-шаблону. Может быть передан несколько раз.",
+            force_flag_desc: "Перезаписывать существующие файлы.",
+            archive_version_arg_desc: "Версия упаковываемого архива. По умолчанию определяется по расширениям файлов данных.",
 
             mode_arg_type: "РЕЖИМ",
             input_path_arg_type: "ВХОДНОЙ_ПУТЬ",
@@ -347,25 +748,130 @@ impl Localization {
             key_arg_type: "КЛЮЧ",
             file_arg_type: "ВХОДНОЙ_ФАЙЛ",
             engine_arg_type: "ДВИЖОК",
+            glob_arg_type: "ШАБЛОН",
+            config_path_arg_type: "ПУТЬ_КОНФИГУРАЦИИ",
+            format_arg_type: "ФОРМАТ",
 
             input_dir_missing: "Входная директория не существует.",
             output_dir_missing: "Выходная директория не существует.",
             translation_dir_missing: r#"Папка "translation" входной директории не существует."#,
 
             elapsed_time_msg: "Затрачено:",
+            summary_msg: "Итог",
+            summary_read_label: "прочитано",
+            summary_written_label: "записано",
+            summary_purged_label: "очищено",
+            summary_skipped_label: "пропущено",
+            summary_unused_label: "не используется",
+            summary_failed_label: "с ошибками",
+            fail_fast_stopped_msg: "Произошла ошибка, обработка остановлена из-за `--fail-fast`.",
+            stats_total_label: "Всего",
+            stats_untranslated_label: "не переведено:",
+            stats_chars_label: "символов",
+            stats_words_label: "слов",
+            missing_delimiter_msg: "В строке отсутствует разделитель `<#>`.",
+            duplicate_delimiter_msg: "Строка содержит больше одного разделителя `<#>`.",
+            missing_source_file_msg: "Файл не существует в исходных данных.",
+            line_not_in_source_msg: "Строка не существует в исходных данных. Возможно, она была изменена, или игра была обновлена.",
+            control_code_mismatch_msg: "Управляющие коды в переводе не совпадают с исходными.",
+            line_break_mismatch_msg: r"Количество переносов строк `\#` в переводе не совпадает с исходным.",
+            leftover_source_script_msg: "Перевод содержит оставшиеся символы исходной письменности.",
+            validation_passed_msg: "Проблем в файлах перевода не найдено.",
+            validation_failed_msg: "Найдено проблем в файлах перевода:",
+            exported_file_msg: "Файл успешно экспортирован.",
+            imported_file_msg: "Импортировано переводов:",
+            exchange_parse_failed_msg: "Не удалось разобрать строку.",
+            exchange_dir_missing_msg: "Директория с файлами для импорта не существует. Используйте команду `export`, чтобы создать её.",
+            exchange_translation_file_missing_msg: "Файл перевода не существует, поэтому перевод для него не может быть импортирован.",
+            exchange_entry_not_found_msg: "Контекст или исходный текст записи не совпадает с текущим файлом перевода, поэтому запись отклонена.",
+            exchange_separator_in_translation_msg: "Перевод содержит разделитель `<#>`, который нельзя сохранить в файле перевода, поэтому запись отклонена.",
+            spreadsheet_column_missing_msg: "В строке заголовков отсутствует обязательный столбец.",
+            dry_run_msg: "Пробный запуск, на диске ничего не изменено",
+            dry_run_created_msg: "Файл будет создан.",
+            dry_run_changed_msg: "Файл будет изменён.",
+            dry_run_unchanged_msg: "Файл останется без изменений.",
+            dry_run_created_label: "создано",
+            dry_run_changed_label: "изменено",
+            dry_run_unchanged_label: "без изменений",
+            dry_run_purged_lines_label: "удалено строк",
+            file_up_to_date_msg: "Исходный файл, файл перевода и записанный файл не изменились с прошлой записи, поэтому запись пропущена.",
+            files_up_to_date_msg: "файлов не изменились с прошлой записи, поэтому их запись пропущена. Используйте --full, чтобы перезаписать их.",
+            watching_msg: "Следим за изменениями в файлах перевода. Нажмите Ctrl+C для остановки.",
+            rebuilt_msg: "Перезаписано",
+            nothing_to_rebuild_msg: "Нечего перезаписывать.",
             custom_processing_enabled_msg: "Индивидуальная обработка текста будет использована для этой игры. Используйте `--disable-custom-processing`, чтобы отключить её.",
+            game_type_msg: "Тип игры",
+            game_type_disabled_reason: "Индивидуальная обработка отключена.",
+            game_type_source_label: "Источник",
+            game_type_detected_reason: "Подошёл профиль игры",
+            game_type_not_detected_reason: "Ни один профиль не подходит для игры",
+            bundled_profile_label: "встроенный",
             enabling_romanize_metadata_msg: "Включаем романизацию текста в соответствии с метаданными из прошлого чтения.",
             disabling_custom_processing_metadata_msg: "Выключаем индивидуальную обработку текста в соответствии с метаданными из прошлого чтения.",
             enabling_trim_metadata_msg: "Включаем удаление лишних пробелов в соответствии с метаданными из прошлого чтения.",
             setting_duplicate_mode_metadata_msg: "Устанавливаем режим дубликатов в соответствии с метаданными из прошлого чтения.",
+            restricting_file_flags_metadata_msg: "Пропускаем файлы, которые не были обработаны в прошлом чтении, в соответствии с метаданными.",
+            metadata_load_failed_msg: "Не удалось загрузить файл метаданных.",
+            metadata_not_object_msg: "Файл метаданных должен содержать JSON объект.",
+            metadata_invalid_field_msg: "Поле метаданных имеет неверное значение.",
+            metadata_missing_field_msg: "Обязательное поле метаданных отсутствует.",
+            metadata_unsupported_version_msg: "Файл метаданных создан более новой версией инструмента. Версия метаданных:",
+            metadata_migrated_msg: "Файл метаданных обновлён до текущей версии:",
+            metadata_engine_mismatch_msg: "Движок игры отличается от записанного в метаданных из прошлого чтения. Убедитесь, что входная директория указана верно.",
+            source_file_changed_msg: "Исходный файл изменился или отсутствует с момента прошлого чтения. Попробуйте прочитать его снова с `--mode append`.",
             no_subcommand_specified_msg: "Команда не была указана. Вызовите `rvpacker-txt-rs -h` для помощи.",
             could_not_determine_game_engine_msg: "Не удалось определить движок игры. Убедитесь, что файл System существует.",
+            www_layout_detected_msg: r#"Обнаружена развёрнутая MV игра, используем директорию "www" как корневую директорию игры."#,
             game_ini_file_missing_msg: "Файл Game.ini не был обнаружен.",
+            game_title_missing_msg: "Не удалось найти название игры в файле System или Game.ini.",
             ignore_file_does_not_exist_msg: "Файл .rvpacker-ignore не существует. Прерываем выполнение.",
             could_not_decrypt_ini_file_msg: "Не удалось расшифровать файл Game.ini. Вы можете вручную конвертировать его в UTF-8, после этого всё заработает.",
-            engine_argument_required_msg: "Аргумент `--engine` необходим.",
-            file_argument_missing_msg: "Аргумент `--file` отсутствует. Он необходим в команде `extract_key`.",
+            engine_undetected_msg: "Не удалось определить движок игры. Передайте его с помощью --engine.",
+            engine_detected_msg: "Движок определён",
             file_argument_is_not_file_msg: "Аргумент `--file` ожидает файл.",
+            config_load_failed_msg: "Не удалось загрузить файл конфигурации.",
+            profiles_dir_missing_msg: "Не удалось прочитать директорию профилей игр.",
+            profile_load_failed_msg: "Не удалось загрузить профиль игры.",
+            archive_missing_msg: "Не удалось найти архив во входной директории. Передайте его путь с помощью --archive.",
+            archive_invalid_header_msg: "Файл не является архивом RGSS.",
+            archive_truncated_msg: "Архив обрезан или повреждён.",
+            archive_unsafe_entry_msg: "Путь файла указывает за пределы выходной директории. Пропускаем.",
+            archive_file_exists_msg: "Файл уже существует. Пропускаем. Используйте --force, чтобы перезаписать его.",
+            archive_extracted_file_msg: "Файл распакован.",
+            archive_entries_label: "файлов",
+            extracted_archive_msg: "Распаковано файлов",
+            packed_archive_msg: "Упаковано файлов",
+            archive_version_undetected_msg: "Не удалось определить версию архива по файлам. Передайте её с помощью --archive-version.",
+            invalid_glob_msg: "Некорректный glob-шаблон.",
+            file_too_large_msg: "Файл слишком большой для архива.",
+            pack_archive_new_engine_msg: "--pack-archive поддерживается только для игр на XP, VX и VX Ace.",
+            archive_not_packed_msg: "Архив не был упакован, так как некоторые файлы не удалось записать.",
+            invalid_key_msg: "Ключ шифрования должен состоять из 32 шестнадцатеричных символов.",
+            key_not_found_msg: "Не удалось найти ключ шифрования: в System.json игры нет encryptionKey, и нет зашифрованных изображений, чтобы получить его из них. Передайте его с помощью --key.",
+            key_from_system_json_msg: "Ключ шифрования прочитан из",
+            key_from_image_msg: "Ключ шифрования получен из",
+            decrypt_game_flag_desc: "Расшифровать все ассеты в директориях img и audio игры, и отключить шифрование ассетов в её System.json, чтобы игра запускалась с обычными ассетами.",
+            encrypt_game_flag_desc: "Зашифровать все ассеты в директориях img и audio игры её ключом, или новым, и включить шифрование ассетов в её System.json.",
+            key_generated_msg: "У игры нет ключа шифрования, сгенерирован новый",
+            remove_originals_flag_desc: "Удалить исходные файлы после обработки, чтобы остались только результаты.",
+            game_file_conflict_msg: "--game обрабатывает всю игру и не может использоваться с --file.",
+            game_system_json_missing_msg: "data/System.json не найден. --game ожидает директорию игры в качестве входной директории.",
+            processed_assets_msg: "Обработано ассетов",
+            new_key_arg_desc: "Новый ключ шифрования. Генерируется случайно, если не передан.",
+            asset_key_mismatch_msg: "Ассет не расшифровывается ключом игры, или повреждён. Ничего не было изменено.",
+            rekey_finished_msg: "Завершено прерванное перешифрование. Игра уже использует новый ключ.",
+            new_key_msg: "Новый ключ шифрования",
+            asset_corrupt_msg: "Файл не является зашифрованным ассетом, или повреждён.",
+            asset_wrong_key_msg: "Ассет не расшифровывается ключом.",
+            asset_unencrypted_msg: "Ассет не зашифрован, хотя игра ожидает, что он зашифрован.",
+            asset_verification_passed_msg: "Все ассеты расшифровываются ключом.",
+            asset_verification_failed_msg: "Найдено проблем в ассетах:",
+            config_invalid_value_msg: "Неверное значение. Разрешённые значения:",
+            config_file_not_found_msg: "Файл конфигурации не найден, используем значения по умолчанию.",
+            default_source: "по умолчанию",
+            config_file_source: "файл конфигурации",
+            command_line_source: "командная строка",
+            metadata_source: "метаданные",
             read_file_msg: "Файл успешно прочитан.",
             written_file_msg: "Файл успешно записан.",
             purged_file_msg: "Файл успешно очищен.",
@@ -1,35 +1,130 @@
+mod archive;
+mod asset;
+mod cache;
+mod config;
+mod data_files;
+mod dry_run;
+mod exchange;
 mod localization;
-use crate::localization::*;
+mod metadata;
+mod output;
+mod po;
+mod profile;
+mod spreadsheet;
+mod stats;
+mod validate;
+mod watch;
+mod xliff;
+use crate::{
+    archive::*, asset::*, cache::*, config::*, data_files::*, dry_run::*,
+    exchange::*, localization::*, metadata::*, output::*, po::*, profile::*,
+    spreadsheet::*, stats::*, validate::*, watch::*, xliff::*,
+};
 use anyhow::{Context, Result, bail};
 use clap::{Arg, ArgAction, Command, crate_version, value_parser};
 use color_print::cformat;
-use rpgmad_lib::Decrypter;
 use rvpacker_lib::{
     json, purge, read, read_to_string_without_bom, types::*, write,
 };
-use serde_json::{Value, from_str, json, to_string};
+use serde_json::{Value, from_str};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     ffi::OsStr,
-    fs::{create_dir_all, read, read_dir, read_to_string, write},
-    io::stdin,
-    mem::transmute,
-    path::PathBuf,
+    fs::{create_dir_all, read, remove_file, write},
+    io::{stderr, stdin, stdout},
+    mem::take,
+    path::{Path, PathBuf},
     process::exit,
     time::Instant,
 };
 use sys_locale::get_locale;
 use tracing::{error, info, warn};
-use tracing_subscriber::{fmt, prelude::*};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    fmt::{self, writer::BoxMakeWriter},
+    prelude::*,
+};
+
+/// Reads `Game.ini` of older engines, trying several encodings.
+fn read_game_ini(
+    input_dir: &Path,
+    localization: &Localization,
+) -> Result<String> {
+    let ini_file_path = &input_dir.join("Game.ini");
+
+    if !ini_file_path.exists() {
+        bail!("{}", localization.game_ini_file_missing_msg)
+    }
+
+    let ini_file_bytes = read(ini_file_path)?;
+    let mut content = String::new();
+
+    for encoding in [
+        encoding_rs::UTF_8,
+        encoding_rs::SHIFT_JIS,
+        encoding_rs::GB18030,
+        encoding_rs::WINDOWS_1252,
+        encoding_rs::WINDOWS_1251,
+    ] {
+        let (decoded, _, error) = encoding.decode(&ini_file_bytes);
+
+        if !error {
+            content = decoded.into_owned();
+        }
+    }
+
+    if content.is_empty() {
+        bail!("{}", localization.could_not_decrypt_ini_file_msg);
+    }
 
-fn get_game_type(game_title: String) -> GameType {
-    let lowercased = game_title.to_lowercase();
+    Ok(content)
+}
+
+/// Parses `key=value` fields of `Game.ini`, with lowercased keys.
+fn parse_ini_fields(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().into()))
+        .collect()
+}
 
-    if lowercased.contains("termina") {
-        GameType::Termina
-    } else if lowercased.contains("lisa") {
-        GameType::LisaRPG
+fn get_game_title(
+    engine_type: EngineType,
+    system_file_path: &Path,
+    input_dir: &Path,
+    localization: &Localization,
+) -> Result<String> {
+    let game_title = if engine_type.is_new() {
+        let system_obj: Value =
+            from_str(&read_to_string_without_bom(system_file_path)?)?;
+        system_obj["gameTitle"]
+            .as_str()
+            .context(localization.game_title_missing_msg)?
+            .into()
     } else {
-        GameType::None
+        read_game_ini(input_dir, localization)?
+            .lines()
+            .find(|line| line.to_lowercase().starts_with("title"))
+            .and_then(|line| line.split_once('='))
+            .context(localization.game_title_missing_msg)?
+            .1
+            .trim()
+            .into()
+    };
+
+    Ok(game_title)
+}
+
+/// MV games, deployed through nw.js, keep all of their content inside `www`
+/// directory, so we treat it as the game root if it's present.
+fn get_game_root(dir: &Path) -> Option<PathBuf> {
+    let www_dir = dir.join("www");
+
+    if www_dir.join("data").exists() || www_dir.join("original").exists() {
+        Some(www_dir)
+    } else {
+        None
     }
 }
 
@@ -37,196 +132,184 @@ fn log_errors(
     results: impl IntoIterator<Item = Result<Outcome, Error>>,
     loc: &Localization,
     verbose: bool,
+    output_format: OutputFormat,
+    summary: &mut Summary,
 ) {
     for result in results {
-        match result {
-            Err(err) => match err {
-                Error::ReadDirFailed { path, err } => {
-                    error!(
-                        "{path}: {loc} ({err})",
-                        path = path.display(),
-                        loc = loc.read_dir_failed_msg
-                    );
-                }
-                Error::AppendModeIsNotSupported => {
-                    error!("{loc}", loc = loc.append_mode_not_supported_msg);
-                }
-                Error::CreateDirFailed { path, err } => {
-                    error!(
-                        "{path}: {loc} ({err})",
-                        path = path.display(),
-                        loc = loc.create_dir_failed_msg
-                    );
-                }
-                Error::JSONParseFailed { file, err } => {
-                    error!(
-                        "{file}: {loc} ({err})",
-                        file = file.display(),
-                        loc = loc.json_parse_failed_msg
-                    );
-                }
-                Error::LoadFailed { file, err } => {
-                    error!(
-                        "{file}: {loc} ({err})",
-                        file = file.display(),
-                        loc = loc.load_failed_msg
-                    );
-                }
-                Error::PluginsFileMissing => {
-                    error!("{loc}", loc = loc.plugins_file_missing_msg);
-                }
-                Error::ReadFileFailed { file, err } => {
-                    error!(
-                        "{file}: {loc} ({err})",
-                        file = file.display(),
-                        loc = loc.read_file_failed_msg
-                    );
-                }
-                Error::WriteFileFailed { file, err } => {
-                    error!(
-                        "{file}: {loc} ({err})",
-                        file = file.display(),
-                        loc = loc.write_file_failed_msg
-                    );
-                }
-            },
-
-            Ok(outcome) => match outcome {
-                // this is quite important, so we always log it
-                Outcome::TXTAlreadyExist(path) => {
-                    info!(
-                        "{path}: {msg}",
-                        path = path.display(),
-                        msg = loc.translation_already_exist_msg
-                    );
-                }
-
-                _ => {
-                    if !verbose {
-                        return;
-                    }
+        summary.add(&result);
+        let event = Event::new(&result, loc);
 
-                    match outcome {
-                        Outcome::TXTAlreadyExist(_) => unreachable!(),
+        if output_format.is_json() {
+            event.emit();
+            continue;
+        }
 
-                        Outcome::MapIsUnused(map) => {
-                            info!(
-                                "{map}: {msg}",
-                                map = map,
-                                msg = loc.map_is_unused_msg
-                            );
-                        }
+        match result {
+            Err(_) => error!("{event}"),
+            // this is quite important, so we always log it
+            Ok(Outcome::TXTAlreadyExist(_)) => info!("{event}"),
+            Ok(_) => {
+                if verbose {
+                    info!("{event}");
+                }
+            }
+        }
+    }
+}
 
-                        Outcome::GeneratedJSON(file) => {
-                            info!(
-                                "{file}: {msg}",
-                                file = file.display(),
-                                msg = loc.generated_json_msg
-                            );
-                        }
+/// Logs only the errors of processing files in a temporary directory, since
+/// the other outcomes refer to files, that don't exist afterwards. They're
+/// still counted as successes for the exit code.
+fn log_temporary_errors(
+    results: impl IntoIterator<Item = Result<Outcome, Error>>,
+    loc: &Localization,
+    output_format: OutputFormat,
+    summary: &mut Summary,
+) {
+    let (errors, successes): (Vec<_>, Vec<_>) =
+        results.into_iter().partition(Result::is_err);
 
-                        Outcome::JSONAlreadyExist(file) => {
-                            info!(
-                                "{file}: {msg}",
-                                file = file.display(),
-                                msg = loc.json_already_exist_msg
-                            );
-                        }
+    for result in &successes {
+        summary.add_simulated(result);
+    }
 
-                        Outcome::MVMZAlreadyJSON => {
-                            info!("{msg}", msg = loc.mvmz_already_json_msg);
-                        }
+    log_errors(errors, loc, false, output_format, summary);
+}
 
-                        Outcome::NoTranslationForEntry { file, entry } => {
-                            info!(
-                                "{entry} ({file}): {msg}",
-                                entry = entry,
-                                msg = loc.no_translation_for_entry_msg
-                            );
-                        }
+/// Takes the results of a single category out of `results`.
+fn take_category(results: &mut FileResults, flag: FileFlags) -> Results {
+    match flag {
+        FileFlags::Map => take(&mut results.map),
+        FileFlags::Other => take(&mut results.other),
+        FileFlags::System => take(&mut results.system),
+        _ => take(&mut results.scripts),
+    }
+}
 
-                        Outcome::PurgedFile(file) => {
-                            info!(
-                                "{file}: {msg}",
-                                file = file.display(),
-                                msg = loc.purged_file_msg
-                            );
-                        }
+/// Processes files of every category in `file_flags` with `process`.
+///
+/// With `fail_fast`, categories are processed one by one, and processing stops
+/// after the first category, which results contain an error. Files of a
+/// category are processed together by the library, so the rest of the files
+/// of that category are processed anyway.
+fn process_files(
+    file_flags: FileFlags,
+    fail_fast: bool,
+    localization: &Localization,
+    mut process: impl FnMut(FileFlags) -> Result<FileResults, Error>,
+) -> Result<Vec<Result<Outcome, Error>>, Error> {
+    if !fail_fast {
+        return Ok(process(file_flags)?.into_iter().collect());
+    }
 
-                        Outcome::ReadFile(file) => {
-                            info!(
-                                "{file}: {msg}",
-                                file = file.display(),
-                                msg = loc.read_file_msg
-                            );
-                        }
+    let mut results = Vec::new();
+
+    for flag in [
+        FileFlags::Map,
+        FileFlags::Other,
+        FileFlags::System,
+        FileFlags::Scripts,
+    ] {
+        if !file_flags.contains(flag) {
+            results.push(Ok(Outcome::NotInFileFlags(flag)));
+            continue;
+        }
 
-                        Outcome::NotInFileFlags(flag) => {
-                            info!(
-                                "{flag:?}: {msg}",
-                                msg = loc.skipped_file_msg
-                            );
-                        }
+        let category_results = take_category(&mut process(flag)?, flag);
 
-                        Outcome::WrittenFile(file) => {
-                            info!(
-                                "{file}: {msg}",
-                                file = file.display(),
-                                msg = loc.written_file_msg
-                            );
-                        }
+        let failed = category_results.iter().any(Result::is_err);
+        results.extend(category_results);
 
-                        Outcome::WrittenJSON(file) => {
-                            info!(
-                                "{file}: {msg}",
-                                file = file.display(),
-                                msg = loc.written_json_msg
-                            );
-                        }
-                    }
-                }
-            },
+        if failed {
+            warn!("{}", localization.fail_fast_stopped_msg);
+            break;
         }
     }
+
+    Ok(results)
+}
+
+/// Returns a bare copy of the subcommand tree, without any arguments, so the
+/// preparse recognizes the same subcommands as the full CLI.
+fn preparse_subcommand(command: &Command) -> Command {
+    Command::new(command.get_name().to_owned())
+        .subcommands(command.get_subcommands().map(preparse_subcommand))
 }
 
-fn preparse_args() -> Language {
+type PreparsedConfig = Option<(PathBuf, Result<Config>)>;
+
+fn preparse_args() -> (Setting<Language>, PreparsedConfig) {
     let preparse = Command::new("preparse")
         .disable_help_flag(true)
         .disable_help_subcommand(true)
         .disable_version_flag(true)
         .ignore_errors(true)
-        .subcommands([
-            Command::new("read"),
-            Command::new("write"),
-            Command::new("purge"),
-            Command::new("json")
-                .subcommands([Command::new("write"), Command::new("generate")]),
-        ])
-        .args([Arg::new("language")
-            .short('l')
-            .long("language")
-            .global(true)
-            .value_parser(["ru", "en"])]);
+        .subcommands(
+            setup_cli(&Localization::new(Language::English))
+                .get_subcommands()
+                .map(preparse_subcommand),
+        )
+        .args([
+            Arg::new("language")
+                .short('l')
+                .long("language")
+                .global(true)
+                .value_parser(LANGUAGES),
+            Arg::new("input-dir")
+                .short('i')
+                .long("input-dir")
+                .global(true)
+                .value_parser(value_parser!(PathBuf))
+                .default_value("./"),
+            Arg::new("config")
+                .long("config")
+                .global(true)
+                .value_parser(value_parser!(PathBuf)),
+        ]);
     let mut preparse_matches = preparse.get_matches();
     let language_arg = preparse_matches.remove_one::<String>("language");
+    let input_dir = unsafe {
+        preparse_matches
+            .remove_one::<PathBuf>("input-dir")
+            .unwrap_unchecked()
+    };
+
+    let config = Config::find(
+        preparse_matches.remove_one::<PathBuf>("config"),
+        &input_dir,
+    )
+    .map(|path| {
+        let config = Config::load(&path);
+        (path, config)
+    });
+
+    let config_language = config
+        .as_ref()
+        .and_then(|(_, config)| config.as_ref().ok())
+        .and_then(|config| config.language.clone());
 
-    let language = language_arg.unwrap_or_else(|| {
+    let (language, source) = if let Some(language) = language_arg {
+        (language, Source::CommandLine)
+    } else if let Some(language) = config_language {
+        (language, Source::ConfigFile)
+    } else {
         let locale = get_locale().unwrap_or(String::from("en-US"));
 
-        if let Some((lang, _)) = locale.split_once('-') {
+        let language = if let Some((lang, _)) = locale.split_once('-') {
             lang.to_owned()
         } else {
             locale
-        }
-    });
+        };
+
+        (language, Source::Default)
+    };
 
     let language = match language.as_str() {
         "ru" | "be" | "uk" => Language::Russian,
         _ => Language::English,
     };
 
-    language
+    (Setting::new(language, source), config)
 }
 
 fn setup_cli(localization: &Localization) -> Command {
@@ -254,7 +337,7 @@ fn setup_cli(localization: &Localization) -> Command {
         .short('r')
         .long("read-mode")
         .alias("mode")
-        .value_parser(["default", "append", "force"])
+        .value_parser(READ_MODES)
         .hide_default_value(true)
         .default_value("default")
         .value_name(localization.mode_arg_type)
@@ -295,6 +378,55 @@ fn setup_cli(localization: &Localization) -> Command {
         .action(ArgAction::SetTrue)
         .display_order(23);
 
+    let dry_run_flag = Arg::new("dry-run")
+        .long("dry-run")
+        .help(localization.dry_run_flag_desc)
+        .action(ArgAction::SetTrue)
+        .display_order(24);
+
+    let full_flag = Arg::new("full")
+        .long("full")
+        .help(localization.full_flag_desc)
+        .action(ArgAction::SetTrue)
+        .display_order(25);
+
+    let watch_flag = Arg::new("watch")
+        .long("watch")
+        .help(localization.watch_flag_desc)
+        .action(ArgAction::SetTrue)
+        .conflicts_with("dry-run")
+        .display_order(26);
+
+    let pack_archive_flag = Arg::new("pack-archive")
+        .long("pack-archive")
+        .help(localization.pack_archive_flag_desc)
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["dry-run", "watch"])
+        .display_order(27);
+
+    let game_type_arg = Arg::new("game-type")
+        .long("game-type")
+        .help(cformat!(
+            "{}\n{} --game-type lisa\n<bold>[{} auto, termina, lisa, none]\n[{} auto]</>",
+            localization.game_type_arg_desc,
+            localization.example,
+            localization.allowed_values,
+            localization.default_value,
+        ))
+        .value_parser(GAME_TYPES)
+        .display_order(92);
+
+    let profiles_dir_arg = Arg::new("profiles-dir")
+        .long("profiles-dir")
+        .help(cformat!(
+            "{}\n{} --profiles-dir ./profiles",
+            localization.profiles_dir_arg_desc,
+            localization.example,
+        ))
+        .value_name(localization.input_path_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(93);
+
     let disable_custom_processing_flag = Arg::new("disable-custom-processing")
         .short('D')
         .long("disable-custom-processing")
@@ -319,7 +451,7 @@ fn setup_cli(localization: &Localization) -> Command {
             localization.allowed_values,
             localization.aliases
         ))
-        .value_parser(["maps", "other", "system", "plugins", "scripts"])
+        .value_parser(PROCESSING_FILES)
         .display_order(94);
 
     let duplicate_mode_arg = Arg::new("duplicate-mode")
@@ -334,7 +466,7 @@ fn setup_cli(localization: &Localization) -> Command {
             localization.default_value,
             localization.aliases,
         ))
-        .value_parser(["allow", "remove"])
+        .value_parser(DUPLICATE_MODES)
         .display_order(93);
 
     let language_arg = Arg::new("language")
@@ -348,9 +480,35 @@ fn setup_cli(localization: &Localization) -> Command {
             localization.example,
             localization.allowed_values,
         ))
-        .value_parser(["en", "ru"])
+        .value_parser(LANGUAGES)
         .display_order(95);
 
+    let config_arg = Arg::new("config")
+        .long("config")
+        .global(true)
+        .help(cformat!(
+            "{}\n<bold>[{} {}]</>",
+            localization.config_arg_desc,
+            localization.default_value,
+            CONFIG_FILE_NAME
+        ))
+        .value_name(localization.config_path_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(3);
+
+    let output_format_arg = Arg::new("output-format")
+        .long("output-format")
+        .global(true)
+        .value_name(localization.format_arg_type)
+        .help(cformat!(
+            "{}\n<bold>[{} text, json]\n[{} text]</>",
+            localization.output_format_arg_desc,
+            localization.allowed_values,
+            localization.default_value,
+        ))
+        .value_parser(OUTPUT_FORMATS)
+        .display_order(96);
+
     let progress_flag = Arg::new("progress")
         .short('P')
         .long("progress")
@@ -367,6 +525,13 @@ fn setup_cli(localization: &Localization) -> Command {
         .help(localization.verbose_arg_desc)
         .display_order(96);
 
+    let fail_fast_flag = Arg::new("fail-fast")
+        .long("fail-fast")
+        .action(ArgAction::SetTrue)
+        .global(true)
+        .help(localization.fail_fast_flag_desc)
+        .display_order(96);
+
     let version_flag = Arg::new("version")
         .short('V')
         .long("version")
@@ -398,6 +563,8 @@ fn setup_cli(localization: &Localization) -> Command {
             &trim_flag,
             &romanize_flag,
             &disable_custom_processing_flag,
+            &game_type_arg,
+            &profiles_dir_arg,
             &disable_processing_arg,
             &duplicate_mode_arg,
         ]);
@@ -406,11 +573,15 @@ fn setup_cli(localization: &Localization) -> Command {
         .disable_help_flag(true)
         .help_template(localization.subcommand_help_template)
         .about(localization.write_command_desc)
+        .args([full_flag, watch_flag, pack_archive_flag])
         .args([
+            &dry_run_flag,
             &help_flag,
             &trim_flag,
             &romanize_flag,
             &disable_custom_processing_flag,
+            &game_type_arg,
+            &profiles_dir_arg,
             &disable_processing_arg,
             &duplicate_mode_arg,
         ]);
@@ -420,15 +591,81 @@ fn setup_cli(localization: &Localization) -> Command {
         .help_template(localization.subcommand_help_template)
         .about(localization.purge_command_desc)
         .args([create_ignore_flag])
+        .args([
+            &dry_run_flag,
+            &help_flag,
+            &trim_flag,
+            &romanize_flag,
+            &disable_custom_processing_flag,
+            &game_type_arg,
+            &profiles_dir_arg,
+            &disable_processing_arg,
+            &duplicate_mode_arg,
+        ]);
+
+    let stats_subcommand = Command::new("stats")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.stats_command_desc)
+        .arg(&help_flag);
+
+    let validate_subcommand = Command::new("validate")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.validate_command_desc)
         .args([
             &help_flag,
             &trim_flag,
             &romanize_flag,
             &disable_custom_processing_flag,
+            &game_type_arg,
+            &profiles_dir_arg,
             &disable_processing_arg,
             &duplicate_mode_arg,
         ]);
 
+    let export_subcommand = Command::new("export")
+        .disable_help_flag(true)
+        .help_template(localization.json_help_template)
+        .about(localization.export_command_desc)
+        .subcommand_required(true)
+        .subcommands([
+            Command::new("po")
+                .about(localization.export_po_command_desc)
+                .disable_help_flag(true),
+            Command::new("xliff")
+                .about(localization.export_xliff_command_desc)
+                .disable_help_flag(true),
+            Command::new("csv")
+                .about(localization.export_csv_command_desc)
+                .disable_help_flag(true),
+            Command::new("tsv")
+                .about(localization.export_tsv_command_desc)
+                .disable_help_flag(true),
+        ])
+        .arg(&help_flag);
+
+    let import_subcommand = Command::new("import")
+        .disable_help_flag(true)
+        .help_template(localization.json_help_template)
+        .about(localization.import_command_desc)
+        .subcommand_required(true)
+        .subcommands([
+            Command::new("po")
+                .about(localization.import_po_command_desc)
+                .disable_help_flag(true),
+            Command::new("xliff")
+                .about(localization.import_xliff_command_desc)
+                .disable_help_flag(true),
+            Command::new("csv")
+                .about(localization.import_csv_command_desc)
+                .disable_help_flag(true),
+            Command::new("tsv")
+                .about(localization.import_tsv_command_desc)
+                .disable_help_flag(true),
+        ])
+        .arg(&help_flag);
+
     let generate_json_subcommand = Command::new("generate")
         .about(localization.generate_json_command_desc)
         .disable_help_flag(true)
@@ -459,15 +696,45 @@ fn setup_cli(localization: &Localization) -> Command {
     let engine_arg = Arg::new("engine")
         .long("engine")
         .help(localization.engine_arg_desc)
-        .value_parser(["mv", "mz"])
+        .value_parser(ENGINES)
         .value_name(localization.engine_arg_type);
 
-    let decrypt_subcommand =
-        Command::new("decrypt").about(localization.decrypt_command_desc);
-    let encrypt_subcommand =
-        Command::new("encrypt").about(localization.encrypt_command_desc);
+    let remove_originals_flag = Arg::new("remove-originals")
+        .long("remove-originals")
+        .help(localization.remove_originals_flag_desc)
+        .action(ArgAction::SetTrue)
+        .requires("game");
+
+    let decrypt_subcommand = Command::new("decrypt")
+        .about(localization.decrypt_command_desc)
+        .args([
+            Arg::new("game")
+                .long("game")
+                .help(localization.decrypt_game_flag_desc)
+                .action(ArgAction::SetTrue),
+            remove_originals_flag.clone(),
+        ]);
+    let encrypt_subcommand = Command::new("encrypt")
+        .about(localization.encrypt_command_desc)
+        .args([
+            Arg::new("game")
+                .long("game")
+                .help(localization.encrypt_game_flag_desc)
+                .action(ArgAction::SetTrue),
+            remove_originals_flag,
+        ]);
     let extract_key_subcommand = Command::new("extract-key")
         .about(localization.extract_key_command_desc);
+    let verify_subcommand =
+        Command::new("verify").about(localization.verify_command_desc);
+    let rekey_subcommand = Command::new("rekey")
+        .about(localization.rekey_command_desc)
+        .arg(
+            Arg::new("new-key")
+                .long("new-key")
+                .help(localization.new_key_arg_desc)
+                .value_name(localization.key_arg_type),
+        );
 
     let asset_subcommand = Command::new("asset")
         .disable_help_flag(true)
@@ -476,10 +743,83 @@ fn setup_cli(localization: &Localization) -> Command {
             decrypt_subcommand,
             encrypt_subcommand,
             extract_key_subcommand,
+            rekey_subcommand,
+            verify_subcommand,
         ])
         .args([key_arg, file_arg, engine_arg])
         .arg(&help_flag);
 
+    let archive_arg = Arg::new("archive")
+        .long("archive")
+        .help(localization.archive_arg_desc)
+        .value_name(localization.file_arg_type)
+        .value_parser(value_parser!(PathBuf));
+
+    let glob_arg = Arg::new("glob")
+        .long("glob")
+        .help(cformat!(
+            "{}\n{} --glob \"Data/*.rvdata2\"",
+            localization.glob_arg_desc,
+            localization.example,
+        ))
+        .value_name(localization.glob_arg_type)
+        .action(ArgAction::Append);
+
+    let force_flag = Arg::new("force")
+        .long("force")
+        .help(localization.force_flag_desc)
+        .action(ArgAction::SetTrue);
+
+    let archive_version_arg = Arg::new("archive-version")
+        .long("archive-version")
+        .help(cformat!(
+            "{}\n<bold>[{} xp, vx, vxace]</>",
+            localization.archive_version_arg_desc,
+            localization.allowed_values,
+        ))
+        .value_parser(ARCHIVE_VERSIONS);
+
+    let list_archive_subcommand = Command::new("list")
+        .about(localization.list_archive_command_desc)
+        .disable_help_flag(true)
+        .args([&archive_arg, &glob_arg, &help_flag]);
+
+    let extract_archive_subcommand = Command::new("extract")
+        .about(localization.extract_archive_command_desc)
+        .disable_help_flag(true)
+        .arg(force_flag)
+        .args([&archive_arg, &glob_arg, &help_flag]);
+
+    let pack_archive_subcommand = Command::new("pack")
+        .about(localization.pack_archive_command_desc)
+        .disable_help_flag(true)
+        .arg(archive_version_arg)
+        .args([&archive_arg, &glob_arg, &help_flag]);
+
+    let archive_subcommand = Command::new("archive")
+        .disable_help_flag(true)
+        .help_template(localization.json_help_template)
+        .about(localization.archive_command_desc)
+        .subcommand_required(true)
+        .subcommands([
+            list_archive_subcommand,
+            extract_archive_subcommand,
+            pack_archive_subcommand,
+        ])
+        .arg(&help_flag);
+
+    let show_config_subcommand = Command::new("show")
+        .about(localization.show_config_command_desc)
+        .disable_help_flag(true);
+
+    let config_subcommand = Command::new("config")
+        .disable_help_flag(true)
+        .help_template(localization.json_help_template)
+        .about(localization.config_command_desc)
+        .subcommand_required(true)
+        .subcommands([show_config_subcommand])
+        .arg(&help_flag);
+
     Command::new("")
         .version(crate_version!())
         .disable_version_flag(true)
@@ -493,26 +833,49 @@ fn setup_cli(localization: &Localization) -> Command {
             read_subcommand,
             write_subcommand,
             purge_subcommand,
+            stats_subcommand,
+            validate_subcommand,
+            export_subcommand,
+            import_subcommand,
             json_subcommand,
             asset_subcommand,
+            archive_subcommand,
+            config_subcommand,
         ])
         .args([
             input_dir_arg,
             output_dir_arg,
+            config_arg,
+            output_format_arg,
             language_arg,
             progress_flag,
             help_flag,
             version_flag,
             verbose_flag,
+            fail_fast_flag,
         ])
         .hide_possible_values(true)
 }
 
 fn main() -> Result<()> {
+    let mut output_format = OutputFormat::Text;
+    let result = run(&mut output_format);
+
+    if let Err(err) = &result {
+        if output_format.is_json() {
+            Event::fatal(err).emit();
+            exit(1);
+        }
+    }
+
+    result
+}
+
+fn run(output_format: &mut OutputFormat) -> Result<()> {
     let mut start_time = Instant::now();
 
-    let language = preparse_args();
-    let localization = Localization::new(language);
+    let (language, config) = preparse_args();
+    let localization = Localization::new(language.value);
     let cli = setup_cli(&localization);
 
     let matches = cli.get_matches();
@@ -522,16 +885,54 @@ fn main() -> Result<()> {
             exit(0);
         });
 
-    let input_dir =
-        unsafe { matches.get_one::<PathBuf>("input-dir").unwrap_unchecked() };
+    let (config_path, config) = match config {
+        Some((path, config)) => {
+            let config = config.with_context(|| {
+                format!(
+                    "{}: {}",
+                    path.display(),
+                    localization.config_load_failed_msg
+                )
+            })?;
+            (Some(path), Some(config))
+        }
+        None => (None, None),
+    };
+
+    let mut all_matches = vec![&matches, subcommand_matches];
+
+    if let Some((_, nested_matches)) = subcommand_matches.subcommand() {
+        all_matches.push(nested_matches);
+    }
+
+    let mut settings = Settings::resolve(
+        language,
+        &all_matches,
+        config.as_ref(),
+        &localization,
+    )
+    .with_context(|| {
+        format!(
+            "{}: {}",
+            config_path.as_deref().unwrap_or(Path::new("")).display(),
+            localization.config_load_failed_msg
+        )
+    })?;
+
+    *output_format = settings.output_format.value;
+    let output_format = *output_format;
+
+    let input_dir = &settings.input_dir.value.clone();
 
     if !input_dir.exists() {
         bail!("{}", localization.input_dir_missing);
     }
 
-    let output_dir = matches
-        .get_one::<PathBuf>("output-dir")
-        .unwrap_or(input_dir);
+    let output_dir = &settings
+        .output_dir
+        .value
+        .clone()
+        .unwrap_or_else(|| input_dir.clone());
 
     if !output_dir.exists() {
         bail!("{}", localization.output_dir_missing)
@@ -545,25 +946,61 @@ fn main() -> Result<()> {
                 .with_level(true)
                 .with_thread_names(false)
                 .with_thread_ids(false)
-                .with_ansi(true),
+                .with_ansi(true)
+                .with_writer(if output_format.is_json() {
+                    // stdout is reserved for events
+                    BoxMakeWriter::new(stderr)
+                } else {
+                    BoxMakeWriter::new(stdout)
+                })
+                // Dependencies, such as file watcher, log their internals with
+                // lower levels.
+                .with_filter(LevelFilter::INFO),
         )
         .init();
 
-    let mut source_path = &input_dir.join("original");
-    let data_path = input_dir.join("data");
+    let www_dir = get_game_root(input_dir);
+
+    if www_dir.is_some() {
+        info!("{}", localization.www_layout_detected_msg);
+    }
+
+    let input_root = www_dir.as_deref().unwrap_or(input_dir);
+    let output_root = &if www_dir.is_some() {
+        output_dir.join("www")
+    } else {
+        output_dir.to_path_buf()
+    };
+
+    let mut source_path = &input_root.join("original");
+    let data_path = input_root.join("data");
 
     if !source_path.exists() {
         source_path = &data_path;
     }
 
-    let translation_path = &output_dir.join("translation");
-    create_dir_all(translation_path)?;
+    let translation_path = &output_root.join("translation");
+
+    // Dry run computes everything in a temporary directory, and must not touch
+    // anything on disk.
+    let dry_run = ["write", "purge"].contains(&subcommand)
+        && subcommand_matches.get_flag("dry-run");
+
+    if !dry_run
+        && ![
+            "asset", "archive", "config", "stats", "validate", "export",
+            "import",
+        ]
+        .contains(&subcommand)
+    {
+        create_dir_all(translation_path)?;
+    }
 
     let metadata_file_path = &translation_path.join(".rvpacker-metadata");
     let ignore_file_path = &translation_path.join(".rvpacker-ignore");
 
-    let progress = matches.get_flag("progress");
-    let verbose = matches.get_flag("verbose");
+    let progress = settings.progress.value;
+    let verbose = settings.verbose.value;
 
     let (engine_type, system_file_path, archive_path) =
         if source_path.join("System.json").exists() {
@@ -592,171 +1029,192 @@ fn main() -> Result<()> {
                 source_path.join("System.rxdata"),
                 Some(input_dir.join("Game.rgssad")),
             )
-        } else if subcommand != "asset" {
+        } else if !["asset", "archive", "config", "stats", "export", "import"]
+            .contains(&subcommand)
+        {
             bail!("{}", localization.could_not_determine_game_engine_msg);
         } else {
             (EngineType::New, source_path.join("System.json"), None)
         };
 
-    let mut game_type = if subcommand == "asset"
-        || ["read", "write", "purge"].contains(&subcommand)
-            && subcommand_matches.get_flag("disable-custom-processing")
-    {
-        GameType::None
+    let game_title =
+        if ["asset", "archive", "config", "stats", "export", "import"]
+            .contains(&subcommand)
+        {
+            None
+        } else if settings.disable_custom_processing.value
+            || settings.game_type.value.is_some()
+        {
+            // Title is only needed for metadata then, so it's not an error if we
+            // cannot get it.
+            get_game_title(
+                engine_type,
+                &system_file_path,
+                input_dir,
+                &localization,
+            )
+            .ok()
+        } else {
+            Some(get_game_title(
+                engine_type,
+                &system_file_path,
+                input_dir,
+                &localization,
+            )?)
+        };
+
+    let mut read_mode = if subcommand == "read" {
+        settings.read_mode.value
     } else {
-        let game_title = if engine_type.is_new() {
-            let system_obj: Value =
-                from_str(&read_to_string_without_bom(&system_file_path)?)?;
-            unsafe {
-                system_obj["gameTitle"].as_str().unwrap_unchecked().into()
+        ReadMode::Default
+    };
+
+    if read_mode.is_append()
+        || ["write", "purge", "validate", "config"].contains(&subcommand)
+    {
+        if let Some(mut metadata) =
+            Metadata::load(metadata_file_path, engine_type, &localization)?
+        {
+            // Only the subcommands, that change the translation, update its
+            // metadata. `read` writes new metadata anyway.
+            if ["write", "purge"].contains(&subcommand) {
+                metadata.migrate(metadata_file_path, &localization)?;
             }
-        } else {
-            let ini_file_path = &input_dir.join("Game.ini");
-
-            if ini_file_path.exists() {
-                let ini_file_bytes = read(ini_file_path)?;
-                let mut content = String::new();
-
-                for encoding in [
-                    encoding_rs::UTF_8,
-                    encoding_rs::SHIFT_JIS,
-                    encoding_rs::GB18030,
-                    encoding_rs::WINDOWS_1252,
-                    encoding_rs::WINDOWS_1251,
-                ] {
-                    let (decoded, _, error) = encoding.decode(&ini_file_bytes);
-
-                    if !error {
-                        content = decoded.into_owned();
-                    }
-                }
 
-                if content.is_empty() {
-                    bail!("{}", localization.could_not_decrypt_ini_file_msg);
-                }
+            if metadata.engine_type != engine_type {
+                warn!("{}", localization.metadata_engine_mismatch_msg);
+            }
 
-                let title_line = unsafe {
-                    content
-                        .lines()
-                        .find(|line| line.to_lowercase().starts_with("title"))
-                        .unwrap_unchecked()
-                };
-                let game_title = unsafe {
-                    title_line
-                        .split_once('=')
-                        .unwrap_unchecked()
-                        .1
-                        .trim()
-                        .into()
+            settings.apply_metadata(
+                &metadata,
+                read_mode.is_append(),
+                &localization,
+            );
+
+            if ["write", "purge"].contains(&subcommand)
+                && !metadata.source_hashes.is_empty()
+            {
+                let source_hashes =
+                    hash_source_files(source_path, engine_type)?;
+
+                // Writing to the input directory overwrites `plugins.js`, and
+                // every other source file without `original` directory, so
+                // their changes are expected.
+                let overwritten = |file: &str| {
+                    subcommand == "write"
+                        && input_root == output_root.as_path()
+                        && (file == PLUGINS_FILE || source_path == &data_path)
                 };
 
-                game_title
-            } else {
-                bail!("{}", localization.game_ini_file_missing_msg)
-            }
-        };
-
-        get_game_type(game_title)
-    };
-
-    if !game_type.is_none() {
-        info!("{}", localization.custom_processing_enabled_msg);
-    }
-
-    let mut read_mode = ReadMode::Default;
-    let mut trim = false;
-    let mut romanize = false;
-    let mut file_flags = FileFlags::all();
-    let mut duplicate_mode = DuplicateMode::Allow;
-
-    if ["read", "write", "purge"].contains(&subcommand) {
-        romanize = subcommand_matches.get_flag("romanize");
-        trim = subcommand_matches.get_flag("trim");
-
-        if let Some(disable_processing_args) =
-            subcommand_matches.get_many::<String>("disable-processing")
-        {
-            for disable_processing_of in disable_processing_args {
-                file_flags.remove(match disable_processing_of.as_str() {
-                    "maps" => FileFlags::Map,
-                    "other" => FileFlags::Other,
-                    "system" => FileFlags::System,
-                    "plugins" | "scripts" => FileFlags::Scripts,
-                    _ => unreachable!(),
-                })
-            }
-        }
+                for (file, hash) in &metadata.source_hashes {
+                    if overwritten(file) {
+                        continue;
+                    }
 
-        if let Some(duplicate_mode_arg) =
-            subcommand_matches.get_one::<String>("duplicate-mode")
-        {
-            if duplicate_mode_arg == "remove" {
-                duplicate_mode = DuplicateMode::Remove;
+                    if source_hashes.get(file) != Some(hash) {
+                        warn!(
+                            "{file}: {}",
+                            localization.source_file_changed_msg
+                        );
+                    }
+                }
             }
         }
     }
 
-    if ["read"].contains(&subcommand) {
-        let mode = subcommand_matches
-            .get_one::<String>("read-mode")
-            .map(|x| x.as_str())
-            .unwrap_or("default");
-
-        read_mode = match mode {
-            "default" => ReadMode::Default,
-            "append" => ReadMode::Append,
-            "force" => ReadMode::Force,
-            _ => unreachable!(),
-        }
-    }
-
-    if read_mode.is_append() || ["write", "purge"].contains(&subcommand) {
-        if let Ok(metadata_file_content) = read_to_string(metadata_file_path) {
-            let metadata: Value = from_str(&metadata_file_content)?;
-            let romanize_metadata =
-                unsafe { metadata["romanize"].as_bool().unwrap_unchecked() };
-            let disable_custom_processing_metadata = unsafe {
-                metadata["disableCustomProcessing"]
-                    .as_bool()
-                    .unwrap_unchecked()
+    // Game type is only relevant for the subcommands, that process the text.
+    let game_type = if ["read", "write", "purge", "validate"]
+        .contains(&subcommand)
+    {
+        let (game_type, reason) = if settings.disable_custom_processing.value {
+            (
+                GameType::None,
+                localization.game_type_disabled_reason.to_string(),
+            )
+        } else if let Some(game_type) = settings.game_type.value {
+            (
+                game_type,
+                format!(
+                    "{}: {}.",
+                    localization.game_type_source_label,
+                    settings.game_type.source.name(&localization)
+                ),
+            )
+        } else {
+            let profiles = load_profiles(
+                settings.profiles_dir.value.as_deref(),
+                &localization,
+            )?;
+            let game_info = GameInfo {
+                title: game_title.as_deref(),
+                ini: if engine_type.is_new() {
+                    BTreeMap::new()
+                } else {
+                    read_game_ini(input_dir, &localization)
+                        .map(|content| parse_ini_fields(&content))
+                        .unwrap_or_default()
+                },
+                root: input_root,
             };
-            let trim_metadata = metadata["trim"].as_bool().unwrap_or(false);
-            let duplicate_mode_metadata = metadata["duplicateMode"].as_u64();
 
-            if romanize_metadata {
-                info!("{}", localization.enabling_romanize_metadata_msg);
-                romanize = romanize_metadata;
-            }
-
-            if disable_custom_processing_metadata && !game_type.is_none() {
-                info!(
-                    "{}",
-                    localization.disabling_custom_processing_metadata_msg
-                );
-                game_type = GameType::None;
+            match find_profile(&profiles, &game_info) {
+                Some(profile) => (
+                    profile.processing.into(),
+                    format!(
+                        "{} {:?}{} ({}).",
+                        localization.game_type_detected_reason,
+                        profile.name,
+                        profile
+                            .description
+                            .as_ref()
+                            .map(|description| format!(" - {description}"))
+                            .unwrap_or_default(),
+                        profile.origin.as_ref().map_or_else(
+                            || localization.bundled_profile_label.into(),
+                            |origin| origin.display().to_string()
+                        )
+                    ),
+                ),
+                None => (
+                    GameType::None,
+                    format!(
+                        "{} {:?}.",
+                        localization.game_type_not_detected_reason,
+                        game_title.as_deref().unwrap_or_default()
+                    ),
+                ),
             }
+        };
 
-            if trim_metadata {
-                info!("{}", localization.enabling_trim_metadata_msg);
-                trim = trim_metadata;
-            }
+        info!(
+            "{}: {}. {reason}",
+            localization.game_type_msg,
+            game_type_name(game_type)
+        );
 
-            if let Some(dup_mode) = duplicate_mode_metadata {
-                info!("{}", localization.setting_duplicate_mode_metadata_msg);
-                duplicate_mode =
-                    unsafe { transmute::<u8, DuplicateMode>(dup_mode as u8) };
-            }
+        if !game_type.is_none() {
+            info!("{}", localization.custom_processing_enabled_msg);
         }
-    }
+
+        game_type
+    } else {
+        GameType::None
+    };
+
+    let romanize = settings.romanize.value;
+    let trim = settings.trim.value;
+    let file_flags = settings.file_flags.value;
+    let duplicate_mode = settings.duplicate_mode.value;
+    let fail_fast = settings.fail_fast.value;
+    let mut summary = Summary::default();
+    let mut exit_code = 0;
 
     match subcommand {
         "read" => {
             use read::*;
 
             let silent = subcommand_matches.get_flag("silent");
-            let ignore = subcommand_matches.get_flag("ignore");
-            let disable_custom_processing =
-                subcommand_matches.get_flag("disable-custom-processing");
+            let ignore = settings.ignore.value;
 
             if read_mode.is_force() && !silent {
                 let start = Instant::now();
@@ -772,28 +1230,36 @@ fn main() -> Result<()> {
                 start_time -= start.elapsed();
             }
 
-            if !read_mode.is_append() {
-                let metadata = json!({ "romanize": romanize,
-                                                "disableCustomProcessing": disable_custom_processing,
-                                                "trim": trim,
-                                                "duplicateMode": duplicate_mode });
-                write(metadata_file_path, to_string(&metadata)?)?;
-            } else if ignore && !ignore_file_path.exists() {
+            if read_mode.is_append() && ignore && !ignore_file_path.exists() {
                 error!("{}", localization.ignore_file_does_not_exist_msg);
                 exit(0);
             }
 
             if let Some(archive_path) = archive_path {
                 if archive_path.exists() && !system_file_path.exists() {
-                    let bytes = read(archive_path)?;
-                    let mut decrypter =
-                        Decrypter::new().force(read_mode.is_force());
-                    decrypter.extract(&bytes, input_dir)?;
+                    Archive::open(&archive_path, &localization)?.extract(
+                        &[],
+                        input_dir,
+                        read_mode.is_force(),
+                        progress,
+                        verbose,
+                        &localization,
+                    )?;
                 }
             }
 
-            let results = ReaderBuilder::new()
-                .with_flags(file_flags)
+            if !read_mode.is_append() {
+                Metadata::new(
+                    engine_type,
+                    game_title,
+                    game_type,
+                    &settings,
+                    hash_source_files(source_path, engine_type)?,
+                )
+                .save(metadata_file_path)?;
+            }
+
+            let mut reader = ReaderBuilder::new()
                 .romanize(romanize)
                 .game_type(game_type)
                 .read_mode(read_mode)
@@ -801,10 +1267,21 @@ fn main() -> Result<()> {
                 .ignore(ignore)
                 .trim(trim)
                 .duplicate_mode(duplicate_mode)
-                .build()
-                .read(source_path, translation_path, engine_type)?;
-
-            log_errors(results, &localization, verbose);
+                .build();
+
+            let results =
+                process_files(file_flags, fail_fast, &localization, |flags| {
+                    reader.set_flags(flags);
+                    reader.read(source_path, translation_path, engine_type)
+                })?;
+
+            log_errors(
+                results,
+                &localization,
+                verbose,
+                output_format,
+                &mut summary,
+            );
         }
         "write" => {
             use write::*;
@@ -813,65 +1290,614 @@ fn main() -> Result<()> {
                 bail!("{}", localization.translation_dir_missing);
             }
 
-            let results = WriterBuilder::new()
-                .with_flags(file_flags)
+            let pack_archive = subcommand_matches.get_flag("pack-archive");
+            let archive_version = ArchiveVersion::from_engine_type(engine_type);
+
+            if pack_archive && archive_version.is_none() {
+                bail!("{}", localization.pack_archive_new_engine_msg);
+            }
+
+            let write_cache_path = &translation_path.join(WRITE_CACHE_FILE);
+            let write_options = format!(
+                "{} {} {} {romanize} {trim} {} {}",
+                crate_version!(),
+                engine_type as u8,
+                game_type as u8,
+                duplicate_mode as u8,
+                output_root.display()
+            );
+
+            let mut writer = WriterBuilder::new()
                 .romanize(romanize)
                 .logging(progress)
                 .game_type(game_type)
                 .trim(trim)
                 .duplicate_mode(duplicate_mode)
-                .build()
-                .write(
-                    source_path,
+                .build();
+
+            // Writes files of the categories from `flags` in `source`
+            // directory to `write_root`.
+            let mut write_source = |source: &Path,
+                                    flags: FileFlags,
+                                    write_root: &Path|
+             -> Result<FileResults, Error> {
+                if www_dir.is_none() || !flags.contains(FileFlags::Scripts) {
+                    writer.set_flags(flags);
+                    return writer.write(
+                        source,
+                        translation_path,
+                        write_root,
+                        engine_type,
+                    );
+                }
+
+                // Writer outputs `plugins.js` to the `js` directory in the
+                // parent of output path, so for `www` layout we have to
+                // write it separately to keep it inside of `www`.
+                writer.set_flags(flags.difference(FileFlags::Scripts));
+                let mut results = writer.write(
+                    source,
                     translation_path,
-                    output_dir,
+                    write_root,
                     engine_type,
                 )?;
 
-            log_errors(results, &localization, verbose);
+                writer.set_flags(FileFlags::Scripts);
+                results.scripts = writer
+                    .write(
+                        source,
+                        translation_path,
+                        &write_root.join("js"),
+                        engine_type,
+                    )?
+                    .scripts;
+
+                Ok(results)
+            };
+
+            // Writes `files`, or every data file of `flags` categories, if
+            // it's `None`, to `write_root`.
+            //
+            // Writer processes whole categories, so to write only some files,
+            // they're copied to a temporary source directory. With
+            // `fail_fast`, files are written one by one this way, and the next
+            // file isn't started after an error.
+            let mut write_files =
+                |flags: FileFlags,
+                 files: Option<&[String]>,
+                 write_root: &Path|
+                 -> Result<Vec<Result<Outcome, Error>>> {
+                    if files.is_none() && !fail_fast {
+                        return Ok(write_source(
+                            source_path,
+                            flags,
+                            write_root,
+                        )?
+                        .into_iter()
+                        .collect());
+                    }
+
+                    let all_files;
+                    let files = match files {
+                        Some(files) => files,
+                        None => {
+                            all_files = collect_data_files(
+                                source_path,
+                                engine_type,
+                                flags,
+                            )?;
+                            &all_files
+                        }
+                    };
+                    let batches: Vec<&[String]> = if fail_fast {
+                        files.chunks(1).collect()
+                    } else {
+                        vec![files]
+                    };
+
+                    let mut results: Vec<_> = CATEGORIES
+                        .into_iter()
+                        .filter(|(flag, _)| !flags.contains(*flag))
+                        .map(|(flag, _)| Ok(Outcome::NotInFileFlags(flag)))
+                        .collect();
+
+                    for batch in batches.into_iter().filter(|b| !b.is_empty()) {
+                        let subset =
+                            SourceSubset::new(source_path, engine_type, batch)?;
+                        let batch_flags: FileFlags = batch
+                            .iter()
+                            .filter_map(|file| get_data_file_category(file))
+                            .collect();
+                        let mut file_results = write_source(
+                            subset.source_path(),
+                            batch_flags,
+                            write_root,
+                        )?;
+                        let mut failed = false;
+
+                        for (flag, _) in CATEGORIES {
+                            if !batch_flags.contains(flag) {
+                                continue;
+                            }
+
+                            for mut result in
+                                take_category(&mut file_results, flag)
+                            {
+                                subset.restore_path(&mut result);
+                                failed |= result.is_err();
+                                results.push(result);
+                            }
+                        }
+
+                        if failed && fail_fast {
+                            warn!("{}", localization.fail_fast_stopped_msg);
+                            break;
+                        }
+                    }
+
+                    Ok(results)
+                };
+
+            if dry_run {
+                // Dry run computes everything, and writes files to the
+                // temporary directory. Writer may also write to the parent of
+                // output path, so it's mirrored.
+                let dry_dir = tempfile::tempdir()?;
+                let dry_output_root = dry_dir.path().join("output");
+
+                // Writer expects data directory to exist, so it's created only
+                // if it exists in the real output directory.
+                let data_dir =
+                    if engine_type.is_new() { "data" } else { "Data" };
+
+                if output_root.join(data_dir).exists() {
+                    create_dir_all(dry_output_root.join(data_dir))?;
+                }
+
+                let results = write_files(file_flags, None, &dry_output_root)?;
+
+                log_temporary_errors(
+                    results,
+                    &localization,
+                    output_format,
+                    &mut summary,
+                );
+
+                let output_parent = output_root.parent().unwrap_or(output_root);
+                let changes = compare_files(
+                    dry_dir.path(),
+                    |file| match file.strip_prefix("output") {
+                        Ok(file) => output_root.join(file),
+                        Err(_) => output_parent.join(file),
+                    },
+                    false,
+                )?;
+
+                report_changes(&changes, &localization, output_format);
+            } else if let Some(version) =
+                archive_version.filter(|_| pack_archive)
+            {
+                // Archive is assembled in a temporary directory from the
+                // contents of the original archive, or the source files, if
+                // there's no archive, and the written files on top of them.
+                let pack_dir = tempfile::tempdir()?;
+                let pack_path = pack_dir.path();
+
+                match archive_path.as_ref().filter(|path| path.exists()) {
+                    Some(archive_path) => {
+                        Archive::open(archive_path, &localization)?.extract(
+                            &[],
+                            pack_path,
+                            true,
+                            progress,
+                            false,
+                            &localization,
+                        )?;
+                    }
+                    None => {
+                        let data_path = pack_path.join("Data");
+                        create_dir_all(&data_path)?;
+                        copy_files(source_path, &data_path)?;
+                    }
+                }
+
+                // Every category is written, since the archive is packed
+                // from scratch.
+                let results = write_files(file_flags, None, pack_path)?;
+                let failed = results.iter().any(Result::is_err);
+
+                log_errors(
+                    results,
+                    &localization,
+                    verbose,
+                    output_format,
+                    &mut summary,
+                );
+
+                if failed {
+                    error!("{}", localization.archive_not_packed_msg);
+                } else {
+                    let archive_path = output_root.join(version.file_name());
+                    let files = collect_pack_files(pack_path)?;
+
+                    pack_to_file(
+                        &files,
+                        version,
+                        &archive_path,
+                        &localization,
+                    )?;
+
+                    info!(
+                        "{}: {} ({})",
+                        localization.packed_archive_msg,
+                        files.len(),
+                        archive_path.display()
+                    );
+                }
+            } else {
+                let mut write_cache = WriteCache::load(write_cache_path);
+
+                // Writes the data files, which source or translation files
+                // changed since the previous write, or every data file with
+                // `full`. With `changed` translation files, only the data
+                // files, written from them, are considered. Returns written
+                // files along with the results.
+                let mut write_changed = |full: bool,
+                                         changed: Option<&BTreeSet<String>>|
+                 -> Result<_> {
+                    let current_cache = WriteCache::new(
+                        &write_options,
+                        &hash_source_files(source_path, engine_type)?,
+                        translation_path,
+                    )?;
+
+                    let all_files = collect_data_files(
+                        source_path,
+                        engine_type,
+                        file_flags,
+                    )?;
+                    let files: Vec<String> = match changed {
+                        Some(changed) => all_files
+                            .iter()
+                            .filter(|file| {
+                                changed.contains(&get_translation_file(file))
+                            })
+                            .cloned()
+                            .collect(),
+                        None => all_files.clone(),
+                    };
+                    let outdated = if full {
+                        files.clone()
+                    } else {
+                        write_cache.get_outdated(&current_cache, &files)
+                    };
+
+                    // Unchanged files are left out by writing the outdated
+                    // ones from a temporary source directory.
+                    let results = if outdated.len() == all_files.len() {
+                        write_files(file_flags, None, output_root)?
+                    } else {
+                        write_files(file_flags, Some(&outdated), output_root)?
+                    };
+
+                    if !results.iter().any(Result::is_err) {
+                        // Written files may overwrite their own sources, like
+                        // `plugins.js` or the files without `original`
+                        // directory, so sources are hashed again.
+                        let current_cache = WriteCache::new(
+                            &write_options,
+                            &hash_source_files(source_path, engine_type)?,
+                            translation_path,
+                        )?;
+                        let written: Vec<PathBuf> = results
+                            .iter()
+                            .filter_map(|result| match result {
+                                Ok(Outcome::WrittenFile(path)) => {
+                                    Some(path.clone())
+                                }
+                                _ => None,
+                            })
+                            .collect();
+
+                        write_cache.update(
+                            &current_cache,
+                            &outdated,
+                            &written,
+                        )?;
+                        write_cache.save(write_cache_path)?;
+                    }
+
+                    let up_to_date: Vec<String> = files
+                        .into_iter()
+                        .filter(|file| !outdated.contains(file))
+                        .collect();
+
+                    Ok((outdated, up_to_date, results))
+                };
+
+                let (_, up_to_date, results) =
+                    write_changed(subcommand_matches.get_flag("full"), None)?;
+
+                if verbose {
+                    for file in &up_to_date {
+                        info!("{file}: {}", localization.file_up_to_date_msg);
+                    }
+                }
+
+                if !up_to_date.is_empty() {
+                    info!(
+                        "{} {}",
+                        up_to_date.len(),
+                        localization.files_up_to_date_msg
+                    );
+                }
+
+                log_errors(
+                    results,
+                    &localization,
+                    verbose,
+                    output_format,
+                    &mut summary,
+                );
+
+                if subcommand_matches.get_flag("watch") {
+                    watch(translation_path, &localization, |changed| {
+                        let start = Instant::now();
+                        let (_, _, results) =
+                            write_changed(false, Some(changed))?;
+
+                        let changed = changed
+                            .iter()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        let written = results
+                            .iter()
+                            .filter_map(|result| match result {
+                                Ok(Outcome::WrittenFile(path)) => path
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy()),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        log_errors(
+                            results,
+                            &localization,
+                            verbose,
+                            output_format,
+                            &mut summary,
+                        );
+
+                        if written.is_empty() {
+                            info!(
+                                "{changed}: {}",
+                                localization.nothing_to_rebuild_msg
+                            );
+                            return Ok(());
+                        }
+
+                        info!(
+                            "{changed}: {} {written} ({:.2}s)",
+                            localization.rebuilt_msg,
+                            start.elapsed().as_secs_f32()
+                        );
+
+                        Ok(())
+                    })?;
+                }
+            }
         }
         "purge" => {
             use purge::*;
-            let create_ignore = subcommand_matches.get_flag("create-ignore");
+            let create_ignore = settings.create_ignore.value;
 
-            let results = PurgerBuilder::new()
-                .with_flags(file_flags)
+            let mut purger = PurgerBuilder::new()
                 .romanize(romanize)
                 .logging(progress)
                 .game_type(game_type)
                 .trim(trim)
                 .duplicate_mode(duplicate_mode)
                 .create_ignore(create_ignore)
+                .build();
+
+            // In dry run, translation files are copied to the temporary
+            // directory and purged there.
+            let dry_dir = if dry_run {
+                let dir = tempfile::tempdir()?;
+                copy_files(translation_path, dir.path())?;
+                Some(dir)
+            } else {
+                None
+            };
+            let dry_translation_path =
+                dry_dir.as_ref().map(|dir| dir.path().to_path_buf());
+            let purge_path =
+                dry_translation_path.as_ref().unwrap_or(translation_path);
+
+            let results =
+                process_files(file_flags, fail_fast, &localization, |flags| {
+                    purger.set_flags(flags);
+                    purger.purge(source_path, purge_path, engine_type)
+                })?;
+
+            if let Some(dry_dir) = dry_dir {
+                log_temporary_errors(
+                    results,
+                    &localization,
+                    output_format,
+                    &mut summary,
+                );
+
+                let changes = compare_files(
+                    dry_dir.path(),
+                    |file| translation_path.join(file),
+                    true,
+                )?;
+
+                report_changes(&changes, &localization, output_format);
+            } else {
+                log_errors(
+                    results,
+                    &localization,
+                    verbose,
+                    output_format,
+                    &mut summary,
+                );
+            }
+        }
+        "stats" => {
+            if !translation_path.exists() {
+                bail!("{}", localization.translation_dir_missing);
+            }
+
+            report_stats(
+                &collect_stats(translation_path)?,
+                &localization,
+                output_format,
+            );
+        }
+        "validate" => {
+            use read::*;
+
+            if !translation_path.exists() {
+                bail!("{}", localization.translation_dir_missing);
+            }
+
+            // Source lines are obtained by reading the game files from scratch
+            // to a temporary directory, with the same options as the
+            // translation was read with.
+            let source_translation_dir = tempfile::tempdir()?;
+            let source_translation_path = source_translation_dir.path();
+
+            let results = ReaderBuilder::new()
+                .with_flags(file_flags)
+                .romanize(romanize)
+                .game_type(game_type)
+                .logging(progress)
+                .trim(trim)
+                .duplicate_mode(duplicate_mode)
                 .build()
-                .purge(source_path, translation_path, engine_type)?;
+                .read(
+                    source_path.as_path(),
+                    source_translation_path,
+                    engine_type,
+                )?;
+
+            log_temporary_errors(
+                results,
+                &localization,
+                output_format,
+                &mut summary,
+            );
+
+            let findings = validate(
+                translation_path,
+                source_translation_path,
+                file_flags,
+            )?;
+
+            for finding in &findings {
+                finding.report(&localization, output_format);
+            }
 
-            log_errors(results, &localization, verbose);
+            if findings.is_empty() {
+                info!("{}", localization.validation_passed_msg);
+            } else {
+                warn!(
+                    "{} {}",
+                    localization.validation_failed_msg,
+                    findings.len()
+                );
+                exit_code = EXIT_VALIDATION_FAILED;
+            }
+        }
+        "export" | "import" => {
+            if !translation_path.exists() {
+                bail!("{}", localization.translation_dir_missing);
+            }
+
+            let format = unsafe {
+                subcommand_matches.subcommand_name().unwrap_unchecked()
+            };
+
+            // Exported files are stored next to the translation directory, in
+            // the directory named after the format.
+            let exchange_path = &output_root.join(format);
+
+            match (subcommand, format) {
+                ("export", "po") => export::<Po>(
+                    translation_path,
+                    exchange_path,
+                    &localization,
+                )?,
+                ("import", "po") => import::<Po>(
+                    exchange_path,
+                    translation_path,
+                    &localization,
+                )?,
+                ("export", "xliff") => export_xliff(
+                    translation_path,
+                    exchange_path,
+                    Metadata::load(
+                        metadata_file_path,
+                        engine_type,
+                        &localization,
+                    )?
+                    .as_ref(),
+                    &localization,
+                )?,
+                ("import", "xliff") => import_xliff(
+                    exchange_path,
+                    translation_path,
+                    &localization,
+                )?,
+                ("export", "csv") => export::<Csv>(
+                    translation_path,
+                    exchange_path,
+                    &localization,
+                )?,
+                ("import", "csv") => import::<Csv>(
+                    exchange_path,
+                    translation_path,
+                    &localization,
+                )?,
+                ("export", "tsv") => export::<Tsv>(
+                    translation_path,
+                    exchange_path,
+                    &localization,
+                )?,
+                ("import", "tsv") => import::<Tsv>(
+                    exchange_path,
+                    translation_path,
+                    &localization,
+                )?,
+                _ => unreachable!(),
+            }
         }
         "json" => {
             use json::*;
-            let (json_subcommand, json_subcommand_matches) =
-                unsafe { subcommand_matches.subcommand().unwrap_unchecked() };
+            let json_subcommand = unsafe {
+                subcommand_matches.subcommand_name().unwrap_unchecked()
+            };
 
             let json_path = input_dir.join("json");
             let json_output_path = input_dir.join("json-output");
 
             match json_subcommand {
                 "generate" => {
-                    let mode = json_subcommand_matches
-                        .get_one::<String>("read-mode")
-                        .map(|x| x.as_str())
-                        .unwrap_or("default");
-
-                    read_mode = match mode {
-                        "default" => ReadMode::Default,
-                        "append" => ReadMode::Append,
-                        "force" => ReadMode::Force,
-                        _ => unreachable!(),
-                    };
+                    read_mode = settings.read_mode.value;
 
                     let results =
                         generate(source_path, &json_path, read_mode, progress);
-                    log_errors(results, &localization, verbose);
+                    log_errors(
+                        results,
+                        &localization,
+                        verbose,
+                        output_format,
+                        &mut summary,
+                    );
                 }
                 "write" => {
                     let results = write(
@@ -880,7 +1906,13 @@ fn main() -> Result<()> {
                         engine_type,
                         progress,
                     );
-                    log_errors(results, &localization, verbose);
+                    log_errors(
+                        results,
+                        &localization,
+                        verbose,
+                        output_format,
+                        &mut summary,
+                    );
                 }
                 _ => unreachable!(),
             }
@@ -888,147 +1920,577 @@ fn main() -> Result<()> {
         "asset" => {
             use asset_decrypter::*;
 
-            let image_subcommand = unsafe {
-                subcommand_matches.subcommand_name().unwrap_unchecked()
+            let Some((image_subcommand, image_matches)) =
+                subcommand_matches.subcommand()
+            else {
+                unreachable!()
             };
 
-            let key = subcommand_matches.get_one::<String>("key");
             let file = subcommand_matches.get_one::<PathBuf>("file");
-            let engine: &str =
-                subcommand_matches
-                    .get_one::<String>("engine")
-                    .context(localization.engine_argument_required_msg)?;
+            let asset_dir =
+                file.and_then(|file| file.parent()).unwrap_or(input_dir);
+
+            if settings
+                .key
+                .value
+                .as_deref()
+                .is_some_and(|key| !is_valid_key(key))
+            {
+                bail!("{}", localization.invalid_key_msg);
+            }
 
-            let mut decrypter = Decrypter::new();
+            // Key is taken from --key, or from the game: `encryptionKey` of
+            // its System.json, or the header of any encrypted image.
+            let find_game_key = || -> Result<String> {
+                if let Some(key) = &settings.key.value {
+                    return Ok(key.to_lowercase());
+                }
 
-            if key.is_none() && matches!(image_subcommand, "encrypt") {
-                decrypter.set_key_from_str(DEFAULT_KEY)?
-            } else {
-                decrypter.set_key_from_str(unsafe { key.unwrap_unchecked() })?
+                let found = match file {
+                    Some(file)
+                        if file.file_name()
+                            == Some(OsStr::new("System.json")) =>
+                    {
+                        read_encryption_key(file)?.map(|key| {
+                            (key, KeySource::SystemJson(file.to_path_buf()))
+                        })
+                    }
+                    Some(file) => {
+                        let is_image = file
+                            .extension()
+                            .and_then(OsStr::to_str)
+                            .is_some_and(|ext| {
+                                ENCRYPTED_IMAGE_EXTENSIONS.contains(&ext)
+                            });
+
+                        // Key of the given image takes precedence over the
+                        // key of the game, that the image is found in.
+                        let image_key = if is_image {
+                            key_from_image(file)?
+                        } else {
+                            None
+                        };
+
+                        match image_key {
+                            Some(key) => Some((
+                                key,
+                                KeySource::Image(file.to_path_buf()),
+                            )),
+                            None => find_key(asset_dir, &[])?,
+                        }
+                    }
+                    None => {
+                        let images: Vec<PathBuf> = collect_assets(
+                            input_dir,
+                            &ENCRYPTED_IMAGE_EXTENSIONS,
+                        )?
+                        .into_iter()
+                        .map(|path| input_dir.join(path))
+                        .collect();
+
+                        find_key(input_dir, &images)?
+                    }
+                };
+
+                let (key, source) =
+                    found.context(localization.key_not_found_msg)?;
+
+                match source {
+                    KeySource::SystemJson(path) => info!(
+                        "{}: {}",
+                        localization.key_from_system_json_msg,
+                        path.display()
+                    ),
+                    KeySource::Image(path) => info!(
+                        "{}: {}",
+                        localization.key_from_image_msg,
+                        path.display()
+                    ),
+                }
+
+                Ok(key)
             };
 
+            let mut decrypter = Decrypter::new();
+
             match image_subcommand {
                 "extract-key" => {
-                    let file =
-                        file.context(localization.file_argument_missing_msg)?;
+                    info!("Encryption key: {}", find_game_key()?);
+                }
 
-                    let filename = file
-                        .file_name()
-                        .context(localization.file_argument_is_not_file_msg)?;
+                "verify" => {
+                    decrypter.set_key_from_str(&find_game_key()?)?;
+
+                    let game_system_json = &input_root.join("data/System.json");
+
+                    // The game the file belongs to, if any, along with the
+                    // path of the file relative to it.
+                    let game_file = file
+                        .filter(|_| game_system_json.exists())
+                        .and_then(|file| {
+                            let root = input_root.canonicalize().ok()?;
+                            let file = file.canonicalize().ok()?;
+                            file.strip_prefix(root).ok().map(Path::to_path_buf)
+                        });
+
+                    // Plain assets are only a problem in the game, that
+                    // expects them to be encrypted.
+                    let (root, paths, images_encrypted, audio_encrypted): (
+                        &Path,
+                        Vec<PathBuf>,
+                        bool,
+                        bool,
+                    ) = if let Some(game_file) = game_file {
+                        let (images_encrypted, audio_encrypted) =
+                            read_encrypted_assets(game_system_json)?;
+
+                        (
+                            input_root,
+                            vec![game_file],
+                            images_encrypted,
+                            audio_encrypted,
+                        )
+                    } else if let Some(file) = file {
+                        let name = file.file_name().unwrap_or(file.as_os_str());
+                        (asset_dir, vec![PathBuf::from(name)], false, false)
+                    } else if game_system_json.exists() {
+                        let (images_encrypted, audio_encrypted) =
+                            read_encrypted_assets(game_system_json)?;
+
+                        (
+                            input_root,
+                            collect_game_assets(
+                                input_root,
+                                &[
+                                    ENCRYPTED_EXTENSIONS.as_slice(),
+                                    &PLAIN_EXTENSIONS,
+                                ]
+                                .concat(),
+                            )?,
+                            images_encrypted,
+                            audio_encrypted,
+                        )
+                    } else {
+                        (
+                            input_dir,
+                            collect_assets(input_dir, &ENCRYPTED_EXTENSIONS)?,
+                            false,
+                            false,
+                        )
+                    };
+
+                    let engine = match settings.engine.value.as_deref() {
+                        Some(engine) => Some(engine),
+                        None => detect_engine(root)?.map(|(engine, _)| engine),
+                    };
+
+                    let mut findings = Vec::new();
+
+                    for path in &paths {
+                        let extension = path
+                            .extension()
+                            .and_then(OsStr::to_str)
+                            .unwrap_or_default();
+
+                        let problem = match extension {
+                            "png" => (images_encrypted
+                                && !(engine == Some("mv")
+                                    && MV_UNENCRYPTED_ASSETS.iter().any(
+                                        |asset| path == Path::new(asset),
+                                    )))
+                            .then_some(AssetProblem::Unencrypted),
+                            "ogg" | "m4a" => audio_encrypted
+                                .then_some(AssetProblem::Unencrypted),
+                            _ => {
+                                verify_asset(&root.join(path), &mut decrypter)?
+                            }
+                        };
+
+                        if let Some(problem) = problem {
+                            findings.push(AssetFinding {
+                                problem,
+                                file: path.display().to_string(),
+                            });
+                        }
+                    }
 
-                    let content: String;
+                    for finding in &findings {
+                        finding.report(&localization, output_format);
+                    }
 
-                    let key = if filename == "System.json" {
-                        content = read_to_string(file)?;
-                        let index = unsafe {
-                            content.rfind("encryptionKey").unwrap_unchecked()
-                        } + "encryptionKey\":".len();
-                        &content[index..].trim().trim_matches('"')[..KEY_LENGTH]
+                    info!(
+                        "{}: {}",
+                        localization.processed_assets_msg,
+                        paths.len()
+                    );
+
+                    if findings.is_empty() {
+                        info!("{}", localization.asset_verification_passed_msg);
                     } else {
-                        let buf = read(file)?;
-                        decrypter.set_key_from_image(&buf);
-                        decrypter.key().unwrap()
-                    };
+                        warn!(
+                            "{} {}",
+                            localization.asset_verification_failed_msg,
+                            findings.len()
+                        );
+                        exit_code = EXIT_VALIDATION_FAILED;
+                    }
+                }
+
+                "rekey" => {
+                    if !input_root.join("data/System.json").exists() {
+                        bail!("{}", localization.game_system_json_missing_msg);
+                    }
 
-                    info!("Encryption key: {key}");
+                    if finish_rekey(input_root)? {
+                        // Game already has the new key then, so it isn't
+                        // re-keyed again.
+                        info!("{}", localization.rekey_finished_msg);
+                    } else {
+                        let new_key =
+                            match image_matches.get_one::<String>("new-key") {
+                                Some(key) if !is_valid_key(key) => {
+                                    bail!("{}", localization.invalid_key_msg)
+                                }
+                                Some(key) => key.to_lowercase(),
+                                None => generate_key(),
+                            };
+
+                        decrypter.set_key_from_str(&find_game_key()?)?;
+
+                        let mut encrypter = Decrypter::new();
+                        encrypter.set_key_from_str(&new_key)?;
+
+                        let assets = collect_game_assets(
+                            input_root,
+                            &ENCRYPTED_EXTENSIONS,
+                        )?;
+
+                        rekey_assets(
+                            input_root,
+                            &assets,
+                            &mut decrypter,
+                            &encrypter,
+                            &localization,
+                        )?;
+
+                        info!("{}: {new_key}", localization.new_key_msg);
+                        info!(
+                            "{}: {}",
+                            localization.processed_assets_msg,
+                            assets.len()
+                        );
+                    }
                 }
 
                 "decrypt" | "encrypt" => {
-                    let mut process_file = |path: &PathBuf,
-                                            filename: &OsStr,
-                                            extension: &str|
-                     -> Result<()> {
-                        let data = read(path)?;
+                    let game = image_matches.get_flag("game");
+                    let remove_originals =
+                        game && image_matches.get_flag("remove-originals");
+                    let game_system_json = &input_root.join("data/System.json");
+
+                    if game {
+                        if file.is_some() {
+                            bail!("{}", localization.game_file_conflict_msg);
+                        }
 
-                        let (processed, new_ext) = match image_subcommand {
-                            "decrypt" => {
-                                let decrypted = decrypter.decrypt(&data);
-                                let new_ext = match extension {
-                                    "rpgmvp" | "png_" => "png",
-                                    "rpgmvo" | "ogg_" => "ogg",
-                                    "rpgmvm" | "m4a_" => "m4a",
-                                    _ => unreachable!(),
-                                };
-                                (decrypted, new_ext)
+                        if !game_system_json.exists() {
+                            bail!(
+                                "{}",
+                                localization.game_system_json_missing_msg
+                            );
+                        }
+                    }
+
+                    // Engine only determines the extensions of encrypted
+                    // assets, so decryption doesn't need it.
+                    let engine = if image_subcommand == "decrypt" {
+                        decrypter.set_key_from_str(&find_game_key()?)?;
+                        ""
+                    } else {
+                        // Game keeps its key, or gets a new one, if it's
+                        // not encrypted yet.
+                        let key = match &settings.key.value {
+                            Some(key) => key.to_lowercase(),
+                            None if game => {
+                                match read_encryption_key(game_system_json)? {
+                                    Some(key) => {
+                                        info!(
+                                            "{}: {}",
+                                            localization
+                                                .key_from_system_json_msg,
+                                            game_system_json.display()
+                                        );
+                                        key
+                                    }
+                                    None => {
+                                        let key = generate_key();
+                                        info!(
+                                            "{}: {key}",
+                                            localization.key_generated_msg
+                                        );
+                                        key
+                                    }
+                                }
                             }
-                            "encrypt" => {
-                                let encrypted = decrypter.encrypt(&data)?;
-                                let new_ext = match (engine, extension) {
-                                    ("mv", "png") => "rpgmvp",
-                                    ("mv", "ogg") => "rpgmvo",
-                                    ("mv", "m4a") => "rpgmvm",
-                                    ("mz", "png") => "png_",
-                                    ("mz", "ogg") => "ogg_",
-                                    ("mz", "m4a") => "m4a_",
-                                    _ => unreachable!(),
+                            None => DEFAULT_KEY.to_owned(),
+                        };
+
+                        decrypter.set_key_from_str(&key)?;
+
+                        match settings.engine.value.as_deref() {
+                            Some(engine) => engine,
+                            None => {
+                                // Plain assets may be encrypted straight into
+                                // the game, so it's checked too.
+                                let detected = match detect_engine(asset_dir)? {
+                                    Some(detected) => Some(detected),
+                                    None => detect_engine(output_dir)?,
                                 };
-                                (encrypted, new_ext)
+                                let (engine, path) = detected.context(
+                                    localization.engine_undetected_msg,
+                                )?;
+
+                                info!(
+                                    "{}: {engine} ({})",
+                                    localization.engine_detected_msg,
+                                    path.display()
+                                );
+                                engine
                             }
+                        }
+                    };
+
+                    let mut process_file = |input_path: &Path,
+                                            output_path: &Path,
+                                            extension: &str|
+                     -> Result<()> {
+                        let data = read(input_path)?;
+
+                        if image_subcommand == "decrypt" && !is_encrypted(&data)
+                        {
+                            bail!(
+                                "{}: {}",
+                                input_path.display(),
+                                localization.asset_corrupt_msg
+                            );
+                        }
+
+                        let (processed, new_ext) = match image_subcommand {
+                            "decrypt" => (
+                                decrypter.decrypt(&data),
+                                decrypted_extension(extension),
+                            ),
+                            "encrypt" => (
+                                decrypter.encrypt(&data)?,
+                                encrypted_extension(engine, extension),
+                            ),
                             _ => unreachable!(),
                         };
 
-                        let output_file = output_dir.join(
-                            PathBuf::from(filename).with_extension(new_ext),
-                        );
-                        write(output_file, processed)?;
+                        let Some(new_ext) = new_ext else {
+                            unreachable!()
+                        };
+
+                        let output_file = output_path.with_extension(new_ext);
 
+                        if let Some(parent) = output_file.parent() {
+                            create_dir_all(parent)?;
+                        }
+
+                        write(output_file, processed)?;
                         Ok(())
                     };
 
                     let exts: &[&str] = match image_subcommand {
-                        "encrypt" => &["png", "ogg", "m4a"],
-                        "decrypt" => &[
-                            "rpgmvp", "rpgmvo", "rpgmvm", "ogg_", "png_",
-                            "m4a_",
-                        ],
+                        "encrypt" => &PLAIN_EXTENSIONS,
+                        "decrypt" => &ENCRYPTED_EXTENSIONS,
                         _ => unreachable!(),
                     };
 
+                    // Input and output paths of every asset.
+                    let mut assets: Vec<(PathBuf, PathBuf)> = Vec::new();
+
                     if let Some(file) = &file {
                         let filename = file.file_name().context(
                             localization.file_argument_is_not_file_msg,
                         )?;
-                        let extension = unsafe {
-                            file.extension()
-                                .unwrap_unchecked()
-                                .to_str()
-                                .unwrap_unchecked()
-                        };
+                        let extension = file
+                            .extension()
+                            .and_then(OsStr::to_str)
+                            .unwrap_or_default();
 
                         if exts.contains(&extension) {
-                            process_file(file, filename, extension)?;
+                            assets.push((
+                                file.to_path_buf(),
+                                output_dir.join(filename),
+                            ));
+                        }
+                    } else if game {
+                        for path in collect_game_assets(input_root, exts)? {
+                            // MV always loads these assets unencrypted.
+                            if engine == "mv"
+                                && image_subcommand == "encrypt"
+                                && MV_UNENCRYPTED_ASSETS
+                                    .iter()
+                                    .any(|asset| path == Path::new(asset))
+                            {
+                                continue;
+                            }
+
+                            assets.push((
+                                input_root.join(&path),
+                                output_root.join(path),
+                            ));
                         }
                     } else {
-                        for entry in read_dir(input_dir)?.flatten() {
-                            let path = entry.path();
-                            let filename = entry.file_name();
-                            let extension = match path.extension() {
-                                Some(ext) => ext,
-                                None => continue,
-                            };
+                        // Subdirectories are mirrored in the output
+                        // directory, so assets with the same name in
+                        // different directories don't overwrite each other.
+                        for path in collect_assets(input_dir, exts)? {
+                            assets.push((
+                                input_dir.join(&path),
+                                output_dir.join(path),
+                            ));
+                        }
+                    }
 
-                            let extension = match extension.to_str() {
-                                Some(ext) => ext,
-                                None => continue,
-                            };
+                    for (input_path, output_path) in &assets {
+                        let extension = input_path
+                            .extension()
+                            .and_then(OsStr::to_str)
+                            .unwrap_or_default();
 
-                            if exts.contains(&extension) {
-                                process_file(&path, &filename, extension)?;
-                            }
+                        process_file(input_path, output_path, extension)?;
+
+                        if remove_originals {
+                            remove_file(input_path)?;
                         }
                     }
+
+                    if game {
+                        // Engine loads plain assets only when encryption is
+                        // turned off, and encrypted assets only when it's
+                        // turned on.
+                        let key = decrypter
+                            .key()
+                            .filter(|_| image_subcommand == "encrypt");
+
+                        set_encrypted_assets(
+                            game_system_json,
+                            &output_root.join("data/System.json"),
+                            key,
+                        )?;
+                    }
+
+                    info!(
+                        "{}: {}",
+                        localization.processed_assets_msg,
+                        assets.len()
+                    );
                 }
                 _ => unreachable!(),
             }
         }
+        "archive" => {
+            let Some((archive_subcommand, archive_matches)) =
+                subcommand_matches.subcommand()
+            else {
+                unreachable!()
+            };
+
+            let globs: Vec<String> = archive_matches
+                .get_many::<String>("glob")
+                .map(|globs| globs.cloned().collect())
+                .unwrap_or_default();
+            let globs = parse_globs(&globs, &localization)?;
+
+            let archive_arg = archive_matches.get_one::<PathBuf>("archive");
+
+            if archive_subcommand == "pack" {
+                let files: Vec<(String, PathBuf)> =
+                    collect_pack_files(input_dir)?
+                        .into_iter()
+                        .filter(|(name, _)| matches_globs(name, &globs))
+                        .collect();
+
+                let version = match archive_matches
+                    .get_one::<String>("archive-version")
+                {
+                    Some(version) => ArchiveVersion::from_name(version),
+                    None => ArchiveVersion::from_files(&files),
+                }
+                .context(localization.archive_version_undetected_msg)?;
+
+                let archive_path = archive_arg
+                    .cloned()
+                    .unwrap_or_else(|| output_dir.join(version.file_name()));
+
+                pack_to_file(&files, version, &archive_path, &localization)?;
+
+                info!(
+                    "{}: {} ({})",
+                    localization.packed_archive_msg,
+                    files.len(),
+                    archive_path.display()
+                );
+            } else {
+                let archive_path = archive_arg
+                    .cloned()
+                    .or_else(|| archive_path.filter(|path| path.exists()))
+                    .context(localization.archive_missing_msg)?;
+
+                let mut archive = Archive::open(&archive_path, &localization)?;
+
+                if archive_subcommand == "list" {
+                    let entries: Vec<&ArchiveEntry> = archive
+                        .entries
+                        .iter()
+                        .filter(|entry| matches_globs(&entry.name, &globs))
+                        .collect();
+
+                    report_entries(&entries, &localization, output_format);
+                } else {
+                    let extracted = archive.extract(
+                        &globs,
+                        output_dir,
+                        archive_matches.get_flag("force"),
+                        progress,
+                        verbose,
+                        &localization,
+                    )?;
+
+                    info!(
+                        "{}: {extracted} ({})",
+                        localization.extracted_archive_msg,
+                        output_dir.display()
+                    );
+                }
+            }
+        }
+        "config" => {
+            settings.show(config_path.as_deref(), &localization);
+            return Ok(());
+        }
         _ => unreachable!(),
     }
 
+    if !["asset", "archive", "stats", "validate", "export", "import"]
+        .contains(&subcommand)
+    {
+        summary.report(&localization, output_format);
+    }
+
     info!(
         "{} {:.2}s",
         localization.elapsed_time_msg,
         start_time.elapsed().as_secs_f32()
     );
 
+    let exit_code = exit_code.max(summary.exit_code());
+
+    if exit_code != 0 {
+        exit(exit_code);
+    }
+
     Ok(())
 }
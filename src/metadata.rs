@@ -0,0 +1,338 @@
+use crate::{config::Settings, localization::*};
+use anyhow::{Context, Result, bail};
+use clap::crate_version;
+use rvpacker_lib::{get_engine_extension, types::*};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, from_str, from_value, to_string_pretty};
+use std::{
+    collections::BTreeMap,
+    fs::{read, read_dir, read_to_string, write},
+    path::Path,
+};
+use tracing::info;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Version of `.rvpacker-metadata` format.
+///
+/// 1 - `romanize`, `disableCustomProcessing`, `trim` and `duplicateMode`,
+/// without the version field.
+///
+/// 2 - Adds `version`, `toolVersion`, `engineType`, `gameTitle`, `fileFlags`
+/// and `sourceHashes`.
+///
+/// 3 - Adds `gameType`.
+pub const METADATA_VERSION: u64 = 3;
+
+/// Options, that translation files were read with, along with the information
+/// about the game they were read from.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub version: u64,
+    pub tool_version: Option<String>,
+    pub engine_type: EngineType,
+    pub game_title: Option<String>,
+    /// Game type, that custom processing was chosen by. `None` for metadata,
+    /// migrated from older versions, in which case it's detected again.
+    pub game_type: Option<GameType>,
+    pub romanize: bool,
+    pub disable_custom_processing: bool,
+    pub trim: bool,
+    pub duplicate_mode: DuplicateMode,
+    pub file_flags: FileFlags,
+    pub source_hashes: BTreeMap<String, String>,
+}
+
+fn field<T: DeserializeOwned>(
+    object: &mut Map<String, Value>,
+    key: &str,
+    localization: &Localization,
+) -> Result<Option<T>> {
+    object
+        .remove(key)
+        .filter(|value| !value.is_null())
+        .map(|value| {
+            from_value(value).with_context(|| {
+                format!("`{key}`: {}", localization.metadata_invalid_field_msg)
+            })
+        })
+        .transpose()
+}
+
+fn required_field<T: DeserializeOwned>(
+    object: &mut Map<String, Value>,
+    key: &str,
+    localization: &Localization,
+) -> Result<T> {
+    field(object, key, localization)?.with_context(|| {
+        format!("`{key}`: {}", localization.metadata_missing_field_msg)
+    })
+}
+
+/// Hashes every source RPG Maker file, so changes in game data since the read
+/// can be detected.
+pub fn hash_source_files(
+    source_path: &Path,
+    engine_type: EngineType,
+) -> Result<BTreeMap<String, String>> {
+    let extension = get_engine_extension(engine_type);
+    let mut hashes = BTreeMap::new();
+
+    for entry in read_dir(source_path)?.flatten() {
+        let path = entry.path();
+
+        if path.extension().is_some_and(|ext| ext == extension) {
+            hashes.insert(
+                entry.file_name().to_string_lossy().into_owned(),
+                format!("{:016x}", xxh3_64(&read(&path)?)),
+            );
+        }
+    }
+
+    if engine_type.is_new() {
+        let plugins_file_path = source_path.with_file_name("js/plugins.js");
+
+        if plugins_file_path.exists() {
+            hashes.insert(
+                "plugins.js".into(),
+                format!("{:016x}", xxh3_64(&read(&plugins_file_path)?)),
+            );
+        }
+    }
+
+    Ok(hashes)
+}
+
+impl Metadata {
+    pub fn new(
+        engine_type: EngineType,
+        game_title: Option<String>,
+        game_type: GameType,
+        settings: &Settings,
+        source_hashes: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            version: METADATA_VERSION,
+            tool_version: Some(crate_version!().into()),
+            engine_type,
+            game_title,
+            game_type: Some(game_type),
+            romanize: settings.romanize.value,
+            disable_custom_processing: settings.disable_custom_processing.value,
+            trim: settings.trim.value,
+            duplicate_mode: settings.duplicate_mode.value,
+            file_flags: settings.file_flags.value,
+            source_hashes,
+        }
+    }
+
+    /// Loads metadata from the file. Metadata of older versions is converted
+    /// in memory, but the file is left as is, until [`Metadata::migrate`]
+    /// is called, so inspecting the translation doesn't change it.
+    ///
+    /// Returns `Ok(None)` if metadata file doesn't exist.
+    pub fn load(
+        path: &Path,
+        engine_type: EngineType,
+        localization: &Localization,
+    ) -> Result<Option<Self>> {
+        let Ok(content) = read_to_string(path) else {
+            return Ok(None);
+        };
+
+        Self::parse(&content, engine_type, localization)
+            .map(Some)
+            .with_context(|| {
+                format!(
+                    "{}: {}",
+                    path.display(),
+                    localization.metadata_load_failed_msg
+                )
+            })
+    }
+
+    /// Saves metadata of an older version to the file in the current
+    /// version.
+    pub fn migrate(
+        &mut self,
+        path: &Path,
+        localization: &Localization,
+    ) -> Result<()> {
+        if self.version >= METADATA_VERSION {
+            return Ok(());
+        }
+
+        info!(
+            "{}: {} {} => {METADATA_VERSION}",
+            path.display(),
+            localization.metadata_migrated_msg,
+            self.version
+        );
+
+        self.version = METADATA_VERSION;
+        self.save(path)
+    }
+
+    fn parse(
+        content: &str,
+        engine_type: EngineType,
+        localization: &Localization,
+    ) -> Result<Self> {
+        let Value::Object(mut object) = from_str(content)? else {
+            bail!("{}", localization.metadata_not_object_msg);
+        };
+
+        let version =
+            field::<u64>(&mut object, "version", localization)?.unwrap_or(1);
+
+        if version > METADATA_VERSION {
+            bail!(
+                "{} {version} > {METADATA_VERSION}",
+                localization.metadata_unsupported_version_msg
+            );
+        }
+
+        let romanize = required_field(&mut object, "romanize", localization)?;
+        let disable_custom_processing = required_field(
+            &mut object,
+            "disableCustomProcessing",
+            localization,
+        )?;
+
+        if version == 1 {
+            return Ok(Self {
+                version,
+                tool_version: None,
+                engine_type,
+                game_title: None,
+                game_type: None,
+                romanize,
+                disable_custom_processing,
+                trim: field(&mut object, "trim", localization)?
+                    .unwrap_or_default(),
+                duplicate_mode: field(
+                    &mut object,
+                    "duplicateMode",
+                    localization,
+                )?
+                .unwrap_or_default(),
+                file_flags: FileFlags::all(),
+                source_hashes: BTreeMap::new(),
+            });
+        }
+
+        Ok(Self {
+            version,
+            tool_version: field(&mut object, "toolVersion", localization)?,
+            engine_type: required_field(
+                &mut object,
+                "engineType",
+                localization,
+            )?,
+            game_title: field(&mut object, "gameTitle", localization)?,
+            game_type: field(&mut object, "gameType", localization)?,
+            romanize,
+            disable_custom_processing,
+            trim: required_field(&mut object, "trim", localization)?,
+            duplicate_mode: required_field(
+                &mut object,
+                "duplicateMode",
+                localization,
+            )?,
+            file_flags: required_field(&mut object, "fileFlags", localization)?,
+            source_hashes: field(&mut object, "sourceHashes", localization)?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write(path, to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCALIZATION: Localization = Localization::new(Language::English);
+
+    const V1: &str = r#"{"romanize":true,"disableCustomProcessing":false,"trim":true,"duplicateMode":1}"#;
+
+    const V2: &str = r#"{
+  "version": 2,
+  "toolVersion": "7.0.0",
+  "engineType": 1,
+  "gameTitle": "Game",
+  "romanize": false,
+  "disableCustomProcessing": true,
+  "trim": false,
+  "duplicateMode": 0,
+  "fileFlags": "Map | System",
+  "sourceHashes": {"System.rvdata2": "0123456789abcdef"}
+}"#;
+
+    #[test]
+    fn migrates_v1_and_v2_only_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".rvpacker-metadata");
+
+        for content in [V1, V2] {
+            write(&path, content).unwrap();
+
+            let mut metadata =
+                Metadata::load(&path, EngineType::VXAce, &LOCALIZATION)
+                    .unwrap()
+                    .unwrap();
+            assert!(metadata.version < METADATA_VERSION);
+            assert!(metadata.game_type.is_none());
+            assert_eq!(read_to_string(&path).unwrap(), content);
+
+            metadata.migrate(&path, &LOCALIZATION).unwrap();
+
+            let migrated =
+                Metadata::load(&path, EngineType::VXAce, &LOCALIZATION)
+                    .unwrap()
+                    .unwrap();
+            assert_eq!(migrated.version, METADATA_VERSION);
+            assert_eq!(migrated.romanize, metadata.romanize);
+            assert_eq!(migrated.trim, metadata.trim);
+            assert_eq!(
+                migrated.duplicate_mode as u8,
+                metadata.duplicate_mode as u8
+            );
+            assert!(migrated.file_flags == metadata.file_flags);
+            assert_eq!(migrated.source_hashes, metadata.source_hashes);
+        }
+    }
+
+    #[test]
+    fn reads_fields_of_v1() {
+        let metadata =
+            Metadata::parse(V1, EngineType::XP, &LOCALIZATION).unwrap();
+
+        assert_eq!(metadata.version, 1);
+        assert!(metadata.engine_type == EngineType::XP);
+        assert!(metadata.romanize && metadata.trim);
+        assert!(matches!(metadata.duplicate_mode, DuplicateMode::Remove));
+        assert!(metadata.file_flags == FileFlags::all());
+    }
+
+    #[test]
+    fn names_missing_and_invalid_fields() {
+        let missing = V2.replace(r#""trim": false,"#, "");
+        let invalid = V2.replace(r#""trim": false"#, r#""trim": "no""#);
+
+        for (content, message) in [
+            (missing, LOCALIZATION.metadata_missing_field_msg),
+            (invalid, LOCALIZATION.metadata_invalid_field_msg),
+        ] {
+            let err =
+                Metadata::parse(&content, EngineType::VXAce, &LOCALIZATION)
+                    .err()
+                    .unwrap();
+
+            assert_eq!(format!("{err}"), format!("`trim`: {message}"));
+        }
+    }
+}
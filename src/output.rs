@@ -0,0 +1,437 @@
+use crate::localization::*;
+use bitflags::parser::to_writer;
+use rvpacker_lib::types::*;
+use serde::Serialize;
+use serde_json::to_string;
+use std::{
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+use tracing::info;
+
+pub const OUTPUT_FORMATS: [&str; 2] = ["text", "json"];
+
+/// Exit code for runs, where some of the files failed to process.
+pub const EXIT_PARTIAL_FAILURE: i32 = 3;
+/// Exit code for runs, where every processed file failed.
+pub const EXIT_TOTAL_FAILURE: i32 = 4;
+/// Exit code for `validate` command, that found problems in translation files.
+pub const EXIT_VALIDATION_FAILED: i32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn from_name(format: &str) -> Self {
+        match format {
+            "text" => Self::Text,
+            "json" => Self::Json,
+            _ => unreachable!(),
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+
+    pub const fn is_json(self) -> bool {
+        matches!(self, Self::Json)
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventType {
+    Outcome,
+    Error,
+    Fatal,
+}
+
+/// A single machine-readable event, printed as one line of JSON in
+/// `--output-format json` mode.
+///
+/// The set of fields is a stable interface: fields are never removed or
+/// renamed, and absent values are serialized as `null`.
+#[derive(Serialize)]
+pub struct Event {
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    /// Name of the `Outcome` or `Error` variant.
+    pub variant: &'static str,
+    pub file: Option<String>,
+    pub entry: Option<String>,
+    /// Localized human-readable description.
+    pub message: String,
+    /// Underlying error message, if any.
+    pub error: Option<String>,
+}
+
+fn display(path: &Path) -> Option<String> {
+    Some(path.display().to_string())
+}
+
+impl Event {
+    fn outcome(
+        variant: &'static str,
+        file: Option<String>,
+        entry: Option<String>,
+        message: &str,
+    ) -> Self {
+        Self {
+            event_type: EventType::Outcome,
+            variant,
+            file,
+            entry,
+            message: message.into(),
+            error: None,
+        }
+    }
+
+    fn error(
+        variant: &'static str,
+        file: Option<String>,
+        message: &str,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            event_type: EventType::Error,
+            variant,
+            file,
+            entry: None,
+            message: message.into(),
+            error,
+        }
+    }
+
+    /// Event for errors, that abort the whole execution.
+    pub fn fatal(error: &anyhow::Error) -> Self {
+        Self {
+            event_type: EventType::Fatal,
+            variant: "Fatal",
+            file: None,
+            entry: None,
+            message: error.to_string(),
+            error: error.root_cause().to_string().into(),
+        }
+    }
+
+    pub fn new(result: &Result<Outcome, Error>, loc: &Localization) -> Self {
+        match result {
+            Err(err) => match err {
+                Error::ReadDirFailed { path, err } => Self::error(
+                    "ReadDirFailed",
+                    display(path),
+                    loc.read_dir_failed_msg,
+                    Some(err.clone()),
+                ),
+                Error::AppendModeIsNotSupported => Self::error(
+                    "AppendModeIsNotSupported",
+                    None,
+                    loc.append_mode_not_supported_msg,
+                    None,
+                ),
+                Error::CreateDirFailed { path, err } => Self::error(
+                    "CreateDirFailed",
+                    display(path),
+                    loc.create_dir_failed_msg,
+                    Some(err.clone()),
+                ),
+                Error::JSONParseFailed { file, err } => Self::error(
+                    "JSONParseFailed",
+                    display(file),
+                    loc.json_parse_failed_msg,
+                    Some(err.clone()),
+                ),
+                Error::LoadFailed { file, err } => Self::error(
+                    "LoadFailed",
+                    display(file),
+                    loc.load_failed_msg,
+                    Some(err.clone()),
+                ),
+                Error::PluginsFileMissing => Self::error(
+                    "PluginsFileMissing",
+                    None,
+                    loc.plugins_file_missing_msg,
+                    None,
+                ),
+                Error::ReadFileFailed { file, err } => Self::error(
+                    "ReadFileFailed",
+                    display(file),
+                    loc.read_file_failed_msg,
+                    Some(err.clone()),
+                ),
+                Error::WriteFileFailed { file, err } => Self::error(
+                    "WriteFileFailed",
+                    display(file),
+                    loc.write_file_failed_msg,
+                    Some(err.clone()),
+                ),
+            },
+
+            Ok(outcome) => match outcome {
+                Outcome::TXTAlreadyExist(path) => Self::outcome(
+                    "TXTAlreadyExist",
+                    display(path),
+                    None,
+                    loc.translation_already_exist_msg,
+                ),
+                Outcome::MapIsUnused(map) => Self::outcome(
+                    "MapIsUnused",
+                    Some(map.clone()),
+                    None,
+                    loc.map_is_unused_msg,
+                ),
+                Outcome::GeneratedJSON(file) => Self::outcome(
+                    "GeneratedJSON",
+                    display(file),
+                    None,
+                    loc.generated_json_msg,
+                ),
+                Outcome::JSONAlreadyExist(file) => Self::outcome(
+                    "JSONAlreadyExist",
+                    display(file),
+                    None,
+                    loc.json_already_exist_msg,
+                ),
+                Outcome::MVMZAlreadyJSON => Self::outcome(
+                    "MVMZAlreadyJSON",
+                    None,
+                    None,
+                    loc.mvmz_already_json_msg,
+                ),
+                Outcome::NoTranslationForEntry { file, entry } => {
+                    Self::outcome(
+                        "NoTranslationForEntry",
+                        Some(file.clone()),
+                        Some(entry.clone()),
+                        loc.no_translation_for_entry_msg,
+                    )
+                }
+                Outcome::PurgedFile(file) => Self::outcome(
+                    "PurgedFile",
+                    display(file),
+                    None,
+                    loc.purged_file_msg,
+                ),
+                Outcome::ReadFile(file) => Self::outcome(
+                    "ReadFile",
+                    display(file),
+                    None,
+                    loc.read_file_msg,
+                ),
+                Outcome::NotInFileFlags(flag) => {
+                    let mut flag_name = String::new();
+                    let _ = to_writer(flag, &mut flag_name);
+
+                    Self::outcome(
+                        "NotInFileFlags",
+                        None,
+                        Some(flag_name),
+                        loc.skipped_file_msg,
+                    )
+                }
+                Outcome::WrittenFile(file) => Self::outcome(
+                    "WrittenFile",
+                    display(file),
+                    None,
+                    loc.written_file_msg,
+                ),
+                Outcome::WrittenJSON(file) => Self::outcome(
+                    "WrittenJSON",
+                    display(file),
+                    None,
+                    loc.written_json_msg,
+                ),
+            },
+        }
+    }
+
+    /// Prints the event as a single line of JSON to stdout.
+    pub fn emit(&self) {
+        // Event consists only of strings, so serialization cannot fail.
+        println!("{}", to_string(self).unwrap_or_default());
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.entry, &self.file) {
+            (Some(entry), Some(file)) => write!(f, "{entry} ({file}): ")?,
+            (Some(subject), None) | (None, Some(subject)) => {
+                write!(f, "{subject}: ")?
+            }
+            (None, None) => {}
+        }
+
+        f.write_str(&self.message)?;
+
+        if let Some(error) = &self.error {
+            write!(f, " ({error})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Event of a command-specific kind, printed as a single line of JSON in
+/// `--output-format json` mode. Fields of `data` follow the `type` field.
+#[derive(Serialize)]
+struct CommandEvent<'a, T: Serialize + ?Sized> {
+    #[serde(rename = "type")]
+    event_type: &'static str,
+    #[serde(flatten)]
+    data: &'a T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+/// Prints `data` as a single line of JSON to stdout, as the event of
+/// `event_type` with the localized `message`, if any.
+pub fn emit_event<T: Serialize + ?Sized>(
+    event_type: &'static str,
+    data: &T,
+    message: Option<&str>,
+) {
+    let event = CommandEvent {
+        event_type,
+        data,
+        message,
+    };
+
+    println!("{}", to_string(&event).unwrap_or_default());
+}
+
+/// Single result of a command, such as a finding or a change, that is logged
+/// in text mode, and printed as a JSON event otherwise.
+pub trait Report: Serialize {
+    const EVENT_TYPE: &str;
+
+    /// Localized description of the result.
+    fn message(&self, loc: &Localization) -> &'static str;
+
+    /// Logs the result in text mode.
+    fn log(&self, message: &str, loc: &Localization);
+
+    /// Logs the result, or prints it as a single line of JSON to stdout.
+    fn report(&self, loc: &Localization, output_format: OutputFormat) {
+        let message = self.message(loc);
+
+        if output_format.is_json() {
+            emit_event(Self::EVENT_TYPE, self, Some(message));
+        } else {
+            self.log(message, loc);
+        }
+    }
+}
+
+/// Counts of processed files, reported at the end of the run.
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct Summary {
+    pub read: usize,
+    pub written: usize,
+    pub purged: usize,
+    pub skipped: usize,
+    pub unused: usize,
+    pub failed: usize,
+    /// Files, that dry run processed successfully in a temporary directory.
+    /// They aren't reported, since nothing was written, but count as
+    /// successes for the exit code.
+    #[serde(skip)]
+    pub simulated: usize,
+}
+
+impl Summary {
+    pub fn add(&mut self, result: &Result<Outcome, Error>) {
+        match result {
+            Err(_) => self.failed += 1,
+            Ok(Outcome::ReadFile(_)) => self.read += 1,
+            Ok(
+                Outcome::WrittenFile(_)
+                | Outcome::WrittenJSON(_)
+                | Outcome::GeneratedJSON(_),
+            ) => self.written += 1,
+            Ok(Outcome::PurgedFile(_)) => self.purged += 1,
+            Ok(
+                Outcome::NotInFileFlags(_)
+                | Outcome::TXTAlreadyExist(_)
+                | Outcome::JSONAlreadyExist(_)
+                | Outcome::MVMZAlreadyJSON,
+            ) => self.skipped += 1,
+            Ok(Outcome::MapIsUnused(_)) => self.unused += 1,
+            Ok(Outcome::NoTranslationForEntry { .. }) => {}
+        }
+    }
+
+    /// Counts the successful result of processing a file in a temporary
+    /// directory.
+    pub fn add_simulated(&mut self, result: &Result<Outcome, Error>) {
+        let mut summary = Self::default();
+        summary.add(result);
+        self.simulated += summary.read + summary.written + summary.purged;
+    }
+
+    /// Returns `0` if nothing failed, [`EXIT_TOTAL_FAILURE`] if nothing
+    /// succeeded, and [`EXIT_PARTIAL_FAILURE`] otherwise.
+    pub const fn exit_code(&self) -> i32 {
+        if self.failed == 0 {
+            0
+        } else if self.read + self.written + self.purged + self.simulated == 0 {
+            EXIT_TOTAL_FAILURE
+        } else {
+            EXIT_PARTIAL_FAILURE
+        }
+    }
+
+    /// Logs the summary, or prints it as a single line of JSON to stdout.
+    pub fn report(&self, loc: &Localization, output_format: OutputFormat) {
+        if output_format.is_json() {
+            emit_event("summary", self, None);
+            return;
+        }
+
+        info!(
+            "{}: {} {}, {} {}, {} {}, {} {}, {} {}, {} {}",
+            loc.summary_msg,
+            loc.summary_read_label,
+            self.read,
+            loc.summary_written_label,
+            self.written,
+            loc.summary_purged_label,
+            self.purged,
+            loc.summary_skipped_label,
+            self.skipped,
+            loc.summary_unused_label,
+            self.unused,
+            loc.summary_failed_label,
+            self.failed,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn counts_dry_run_successes_for_exit_code() {
+        let mut summary = Summary {
+            failed: 1,
+            ..Default::default()
+        };
+        assert_eq!(summary.exit_code(), EXIT_TOTAL_FAILURE);
+
+        summary.add_simulated(&Ok(Outcome::WrittenFile(PathBuf::new())));
+        summary.add_simulated(&Ok(Outcome::NotInFileFlags(FileFlags::Map)));
+        assert_eq!(summary.simulated, 1);
+        assert_eq!(summary.written, 0);
+        assert_eq!(summary.exit_code(), EXIT_PARTIAL_FAILURE);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{exchange::*, localization::*};
+use anyhow::{Context, Result, bail};
+use clap::crate_version;
+use std::{fmt::Write, mem::take};
+
+/// gettext PO catalog.
+///
+/// `msgctxt` carries the entry context, and `#:` reference points to the
+/// line in the translation file. Fuzzy entries aren't imported, following the
+/// gettext convention.
+pub struct Po;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '\\' => escaped.push_str(r"\\"),
+            '"' => escaped.push_str(r#"\""#),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\t' => escaped.push_str(r"\t"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+
+        unescaped.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            other => other,
+        });
+    }
+
+    Some(unescaped)
+}
+
+fn write_field(output: &mut String, keyword: &str, text: &str) {
+    if !text.contains('\n') {
+        let _ = writeln!(output, "{keyword} \"{}\"", escape(text));
+        return;
+    }
+
+    let _ = writeln!(output, "{keyword} \"\"");
+
+    for line in text.split_inclusive('\n') {
+        let _ = writeln!(output, "\"{}\"", escape(line));
+    }
+}
+
+/// Parses a quoted PO string.
+fn unquote(text: &str) -> Option<String> {
+    unescape(text.trim().strip_prefix('"')?.strip_suffix('"')?)
+}
+
+#[derive(Default)]
+struct PoEntry {
+    context: String,
+    id: Option<String>,
+    translation: Option<String>,
+    fuzzy: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Context,
+    Id,
+    Translation,
+    Ignored,
+}
+
+impl Format for Po {
+    const EXTENSION: &str = "po";
+
+    fn export(name: &str, entries: &[Entry]) -> String {
+        let mut output = String::new();
+
+        write_field(&mut output, "msgid", "");
+        write_field(
+            &mut output,
+            "msgstr",
+            &format!(
+                "Content-Type: text/plain; charset=UTF-8\nContent-Transfer-Encoding: 8bit\nX-Generator: rvpacker-txt-rs {}\n",
+                crate_version!()
+            ),
+        );
+
+        for entry in entries {
+            let _ = writeln!(output, "\n#: {name}.txt:{}", entry.line);
+            write_field(&mut output, "msgctxt", &entry.context);
+            write_field(&mut output, "msgid", &entry.source);
+            write_field(&mut output, "msgstr", &entry.translation);
+        }
+
+        output
+    }
+
+    fn parse(
+        content: &str,
+        localization: &Localization,
+    ) -> Result<Vec<(EntryKey, String)>> {
+        let mut translations = Vec::new();
+        let mut entry = PoEntry::default();
+        let mut field = Field::Ignored;
+
+        let mut finish = |entry: PoEntry| {
+            // Header entry has empty `msgid`, and is skipped too.
+            if let (Some(id), Some(translation)) = (entry.id, entry.translation)
+            {
+                if !id.is_empty() && !entry.fuzzy {
+                    translations.push(((entry.context, id), translation));
+                }
+            }
+        };
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            let parse = || {
+                unquote(line.split_once(' ').map_or("", |(_, text)| text))
+                    .with_context(|| {
+                        format!(
+                            "{}: {}",
+                            index + 1,
+                            localization.exchange_parse_failed_msg
+                        )
+                    })
+            };
+
+            if line.is_empty() {
+                finish(take(&mut entry));
+                field = Field::Ignored;
+            } else if line.starts_with("#,") {
+                entry.fuzzy |= line.contains("fuzzy");
+            } else if line.starts_with('#') {
+                // Other comments and obsolete entries.
+            } else if line.starts_with("msgctxt ") || line.starts_with("msgid ")
+            {
+                if entry.translation.is_some() {
+                    finish(take(&mut entry));
+                }
+
+                if line.starts_with("msgctxt ") {
+                    entry.context = parse()?;
+                    field = Field::Context;
+                } else {
+                    entry.id = Some(parse()?);
+                    field = Field::Id;
+                }
+            } else if line.starts_with("msgstr ")
+                || line.starts_with("msgstr[0] ")
+            {
+                entry.translation = Some(parse()?);
+                field = Field::Translation;
+            } else if line.starts_with("msgid_plural ")
+                || line.starts_with("msgstr[")
+            {
+                field = Field::Ignored;
+            } else if line.starts_with('"') {
+                let text = unquote(line).with_context(|| {
+                    format!(
+                        "{}: {}",
+                        index + 1,
+                        localization.exchange_parse_failed_msg
+                    )
+                })?;
+
+                match field {
+                    Field::Context => entry.context.push_str(&text),
+                    Field::Id => {
+                        entry.id.get_or_insert_default().push_str(&text)
+                    }
+                    Field::Translation => entry
+                        .translation
+                        .get_or_insert_default()
+                        .push_str(&text),
+                    Field::Ignored => {}
+                }
+            } else {
+                bail!(
+                    "{}: {}",
+                    index + 1,
+                    localization.exchange_parse_failed_msg
+                );
+            }
+        }
+
+        finish(entry);
+        Ok(translations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::tests::*;
+
+    #[test]
+    fn round_trips_translation_file() {
+        assert_eq!(round_trip::<Po>(), TRANSLATION);
+    }
+
+    #[test]
+    fn escapes_special_characters() {
+        let text = "Say \"hi\"\tand\\or\r\nleave";
+        assert_eq!(unescape(&escape(text)).as_deref(), Some(text));
+    }
+
+    #[test]
+    fn skips_fuzzy_and_header_entries() {
+        let content = "msgid \"\"
+msgstr \"Content-Type: text/plain; charset=UTF-8\\n\"
+
+#, fuzzy
+msgctxt \"maps\"
+msgid \"Hello\"
+msgstr \"Привет\"
+
+msgctxt \"maps\"
+msgid \"\"
+\"Good\\n\"
+\"bye\"
+msgstr \"Пока\"
+";
+        let translations =
+            Po::parse(content, &Localization::new(Language::English)).unwrap();
+
+        assert_eq!(
+            translations,
+            [(
+                (String::from("maps"), String::from("Good\nbye")),
+                String::from("Пока")
+            )]
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use crate::localization::*;
+use anyhow::{Context, Result};
+use rvpacker_lib::types::GameType;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs::{read, read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Profiles, that are bundled with the program.
+const BUNDLED_PROFILES: &str = include_str!("../profiles/bundled.toml");
+
+/// Custom text processing, that profile picks.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Processing {
+    None,
+    Termina,
+    Lisa,
+}
+
+impl From<Processing> for GameType {
+    fn from(processing: Processing) -> Self {
+        match processing {
+            Processing::None => Self::None,
+            Processing::Termina => Self::Termina,
+            Processing::Lisa => Self::LisaRPG,
+        }
+    }
+}
+
+/// Conditions, that must all be met for the game to match.
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct Match {
+    /// Exact game title, case-insensitive.
+    title: Option<String>,
+    /// Words, that the title contains as whole words, case-insensitive.
+    title_words: Vec<String>,
+    /// Words, that the title starts with, case-insensitive.
+    title_first_words: Vec<String>,
+    /// Fields of `Game.ini` with their values. Field names are
+    /// case-insensitive.
+    ini: BTreeMap<String, String>,
+    /// Files, that exist in the game, relative to its root.
+    files: Vec<PathBuf>,
+    /// Hexadecimal xxh3 hashes of the files, relative to the game root.
+    fingerprints: BTreeMap<PathBuf, String>,
+}
+
+/// Information about the game, that profiles are matched against.
+pub struct GameInfo<'a> {
+    pub title: Option<&'a str>,
+    /// Fields of `Game.ini` with lowercased names.
+    pub ini: BTreeMap<String, String>,
+    pub root: &'a Path,
+}
+
+/// Splits the text to lowercased words.
+fn get_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl Match {
+    fn is_match(&self, game: &GameInfo) -> bool {
+        // Table without conditions would match every game.
+        if self.title.is_none()
+            && self.title_words.is_empty()
+            && self.title_first_words.is_empty()
+            && self.ini.is_empty()
+            && self.files.is_empty()
+            && self.fingerprints.is_empty()
+        {
+            return false;
+        }
+
+        let title = game.title.unwrap_or_default();
+        let title_words = get_words(title);
+
+        let first_words: Vec<String> = self
+            .title_first_words
+            .iter()
+            .flat_map(|word| get_words(word))
+            .collect();
+
+        self.title.as_ref().is_none_or(|expected| {
+            expected.trim().eq_ignore_ascii_case(title.trim())
+        }) && self
+            .title_words
+            .iter()
+            .flat_map(|word| get_words(word))
+            .all(|word| title_words.contains(&word))
+            && title_words.starts_with(&first_words)
+            && self.ini.iter().all(|(field, value)| {
+                game.ini.get(&field.to_lowercase()) == Some(value)
+            })
+            && self.files.iter().all(|file| game.root.join(file).exists())
+            && self.fingerprints.iter().all(|(file, hash)| {
+                read(game.root.join(file)).is_ok_and(|content| {
+                    format!("{:016x}", xxh3_64(&content))
+                        .eq_ignore_ascii_case(hash)
+                })
+            })
+    }
+}
+
+/// Game profile, that picks custom text processing for the games it matches.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub processing: Processing,
+    #[serde(rename = "match", default)]
+    matches: Vec<Match>,
+    /// File, that the profile was loaded from, or `None` for bundled
+    /// profiles.
+    #[serde(skip)]
+    pub origin: Option<PathBuf>,
+}
+
+impl Profile {
+    /// Whether any of the `match` tables of the profile matches the game.
+    pub fn is_match(&self, game: &GameInfo) -> bool {
+        self.matches.iter().any(|matcher| matcher.is_match(game))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    #[serde(default)]
+    profile: Vec<Profile>,
+}
+
+fn parse_profiles(
+    content: &str,
+    origin: Option<&Path>,
+) -> Result<Vec<Profile>> {
+    let mut profiles = toml::from_str::<ProfileFile>(content)?.profile;
+
+    for profile in &mut profiles {
+        profile.origin = origin.map(Path::to_path_buf);
+    }
+
+    Ok(profiles)
+}
+
+/// Loads profiles from every `.toml` file in `profiles_dir`, sorted by file
+/// name, followed by the bundled profiles, so profiles from the directory
+/// take precedence.
+pub fn load_profiles(
+    profiles_dir: Option<&Path>,
+    localization: &Localization,
+) -> Result<Vec<Profile>> {
+    let mut profiles = Vec::new();
+
+    if let Some(profiles_dir) = profiles_dir {
+        let mut files: Vec<PathBuf> = read_dir(profiles_dir)
+            .with_context(|| {
+                format!(
+                    "{}: {}",
+                    profiles_dir.display(),
+                    localization.profiles_dir_missing_msg
+                )
+            })?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+
+        files.sort_unstable();
+
+        for file in files {
+            profiles.extend(
+                parse_profiles(&read_to_string(&file)?, Some(&file))
+                    .with_context(|| {
+                        format!(
+                            "{}: {}",
+                            file.display(),
+                            localization.profile_load_failed_msg
+                        )
+                    })?,
+            );
+        }
+    }
+
+    profiles.extend(parse_profiles(BUNDLED_PROFILES, None)?);
+    Ok(profiles)
+}
+
+/// Returns the first profile, that matches the game.
+pub fn find_profile<'a>(
+    profiles: &'a [Profile],
+    game: &GameInfo,
+) -> Option<&'a Profile> {
+    profiles.iter().find(|profile| profile.is_match(game))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+
+    const LOCALIZATION: Localization = Localization::new(Language::English);
+
+    fn game<'a>(title: &'a str, root: &'a Path) -> GameInfo<'a> {
+        GameInfo {
+            title: Some(title),
+            ini: BTreeMap::new(),
+            root,
+        }
+    }
+
+    fn parse_match(content: &str) -> Match {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn matches_title_rules() {
+        let root = Path::new("");
+        let title = parse_match(r#"title = "My Game""#);
+        let words = parse_match(r#"title-words = ["game", "my"]"#);
+        let first_words = parse_match(r#"title-first-words = ["my game"]"#);
+
+        assert!(title.is_match(&game(" my game ", root)));
+        assert!(!title.is_match(&game("My Game 2", root)));
+
+        assert!(words.is_match(&game("Game: The My", root)));
+        assert!(!words.is_match(&game("My Gamer", root)));
+
+        assert!(first_words.is_match(&game("MY GAME: Deluxe", root)));
+        assert!(!first_words.is_match(&game("Not My Game", root)));
+        assert!(!first_words.is_match(&game("Mygame", root)));
+    }
+
+    #[test]
+    fn matches_ini_rule() {
+        let root = Path::new("");
+        let ini = parse_match(r#"ini = { Library = "RGSS301.dll" }"#);
+        let mut game = game("Game", root);
+
+        assert!(!ini.is_match(&game));
+
+        game.ini.insert("library".into(), "RGSS301.dll".into());
+        assert!(ini.is_match(&game));
+
+        game.ini.insert("library".into(), "RGSS300.dll".into());
+        assert!(!ini.is_match(&game));
+    }
+
+    #[test]
+    fn matches_file_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root.join("Game.exe"), "content").unwrap();
+
+        let hash = format!("{:016x}", xxh3_64(b"content"));
+        let files = parse_match(r#"files = ["Game.exe"]"#);
+        let missing = parse_match(r#"files = ["Game.exe", "Other.exe"]"#);
+        let fingerprint = parse_match(&format!(
+            r#"fingerprints = {{ "Game.exe" = "{}" }}"#,
+            hash.to_uppercase()
+        ));
+        let wrong_fingerprint = parse_match(
+            r#"fingerprints = { "Game.exe" = "0123456789abcdef" }"#,
+        );
+
+        assert!(files.is_match(&game("Game", root)));
+        assert!(!missing.is_match(&game("Game", root)));
+        assert!(fingerprint.is_match(&game("Game", root)));
+        assert!(!wrong_fingerprint.is_match(&game("Game", root)));
+    }
+
+    #[test]
+    fn requires_every_condition_and_at_least_one() {
+        let root = Path::new("");
+        let both = parse_match(
+            r#"title-words = ["game"]
+files = ["Missing.exe"]"#,
+        );
+
+        assert!(!both.is_match(&game("Game", root)));
+        assert!(!Match::default().is_match(&game("Game", root)));
+    }
+
+    #[test]
+    fn parses_bundled_profiles() {
+        let profiles = parse_profiles(BUNDLED_PROFILES, None).unwrap();
+        let names: Vec<&str> = profiles
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["termina", "lisa"]);
+        assert!(profiles.iter().all(|profile| !profile.matches.is_empty()));
+    }
+
+    /// Bundled profiles must pick the processing by whole words of the
+    /// title, and not by substrings.
+    #[test]
+    fn detects_bundled_game_types() {
+        let profiles = parse_profiles(BUNDLED_PROFILES, None).unwrap();
+        let root = Path::new("");
+        let detect = |title| {
+            find_profile(&profiles, &game(title, root))
+                .map(|profile| profile.name.as_str())
+        };
+
+        assert_eq!(detect("Fear & Hunger 2: Termina"), Some("termina"));
+        assert_eq!(detect("TERMINA"), Some("termina"));
+        assert_eq!(detect("LISA: The Painful"), Some("lisa"));
+        assert_eq!(
+            detect("Lisa the Joyful - Definitive Edition"),
+            Some("lisa")
+        );
+        assert_eq!(detect("LISA"), Some("lisa"));
+
+        assert_eq!(detect("Terminal Velocity"), None);
+        assert_eq!(detect("Mona Lisa's Smile"), None);
+        assert_eq!(detect("Elisa Adventure"), None);
+        assert_eq!(detect("Lisandra"), None);
+    }
+
+    #[test]
+    fn loads_directory_profiles_before_bundled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("termina.toml");
+        write(
+            &path,
+            r#"[[profile]]
+name = "not-termina"
+processing = "none"
+
+[[profile.match]]
+title = "Fear & Hunger 2: Termina"
+"#,
+        )
+        .unwrap();
+        write(dir.path().join("readme.txt"), "not a profile").unwrap();
+
+        let profiles = load_profiles(Some(dir.path()), &LOCALIZATION).unwrap();
+        let profile = find_profile(
+            &profiles,
+            &game("Fear & Hunger 2: Termina", dir.path()),
+        )
+        .unwrap();
+
+        assert_eq!(profile.name, "not-termina");
+        assert_eq!(profile.origin.as_deref(), Some(path.as_path()));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(
+            parse_profiles(
+                r#"[[profile]]
+name = "game"
+processing = "none"
+
+[[profile.match]]
+title-word = ["game"]
+"#,
+                None,
+            )
+            .is_err()
+        );
+        assert!(
+            parse_profiles(
+                "[[profile]]\nname = \"game\"\nprocessing = \"unknown\"\n",
+                None
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{exchange::*, localization::*};
+use anyhow::{Context, Result};
+use csv::{ReaderBuilder, WriterBuilder};
+
+const LOCATION_COLUMN: &str = "location";
+const CONTEXT_COLUMN: &str = "context";
+const SOURCE_COLUMN: &str = "source";
+const TRANSLATION_COLUMN: &str = "translation";
+
+/// Byte order mark, that makes spreadsheet editors detect UTF-8 encoding.
+const BOM: &str = "\u{FEFF}";
+
+/// Spreadsheet with comma-separated values.
+pub struct Csv;
+
+/// Spreadsheet with tab-separated values.
+pub struct Tsv;
+
+/// Writes entries as a spreadsheet with `location`, `context`, `source` and
+/// `translation` columns.
+///
+/// Line breaks are kept as real line breaks inside quoted cells.
+fn export_delimited(name: &str, entries: &[Entry], delimiter: u8) -> String {
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(BOM.as_bytes().to_vec());
+
+    // Writing to `Vec` cannot fail.
+    let _ = writer.write_record([
+        LOCATION_COLUMN,
+        CONTEXT_COLUMN,
+        SOURCE_COLUMN,
+        TRANSLATION_COLUMN,
+    ]);
+
+    for entry in entries {
+        let _ = writer.write_record([
+            &format!("{name}.txt:{}", entry.line),
+            &entry.context,
+            &entry.source,
+            &entry.translation,
+        ]);
+    }
+
+    let bytes = writer.into_inner().unwrap_or_default();
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+/// Parses a spreadsheet by the names of its columns, so columns may be
+/// reordered and other columns may be added.
+fn parse_delimited(
+    content: &str,
+    delimiter: u8,
+    localization: &Localization,
+) -> Result<Vec<(EntryKey, String)>> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(content.trim_start_matches(BOM).as_bytes());
+
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .with_context(|| {
+                format!(
+                    "`{name}`: {}",
+                    localization.spreadsheet_column_missing_msg
+                )
+            })
+    };
+
+    let context_column = column(CONTEXT_COLUMN)?;
+    let source_column = column(SOURCE_COLUMN)?;
+    let translation_column = column(TRANSLATION_COLUMN)?;
+
+    let mut translations = Vec::new();
+
+    for record in reader.records() {
+        let record = record?;
+        let cell = |index| record.get(index).unwrap_or_default().to_string();
+
+        translations.push((
+            (cell(context_column), cell(source_column)),
+            cell(translation_column),
+        ));
+    }
+
+    Ok(translations)
+}
+
+impl Format for Csv {
+    const EXTENSION: &str = "csv";
+
+    fn export(name: &str, entries: &[Entry]) -> String {
+        export_delimited(name, entries, b',')
+    }
+
+    fn parse(
+        content: &str,
+        localization: &Localization,
+    ) -> Result<Vec<(EntryKey, String)>> {
+        parse_delimited(content, b',', localization)
+    }
+}
+
+impl Format for Tsv {
+    const EXTENSION: &str = "tsv";
+
+    fn export(name: &str, entries: &[Entry]) -> String {
+        export_delimited(name, entries, b'\t')
+    }
+
+    fn parse(
+        content: &str,
+        localization: &Localization,
+    ) -> Result<Vec<(EntryKey, String)>> {
+        parse_delimited(content, b'\t', localization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::tests::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn round_trips_csv() {
+        assert_eq!(round_trip::<Csv>(), TRANSLATION);
+    }
+
+    #[test]
+    fn round_trips_tsv() {
+        assert_eq!(round_trip::<Tsv>(), TRANSLATION);
+    }
+
+    #[test]
+    fn refuses_rows_with_changed_source() {
+        let localization = Localization::new(Language::English);
+        let exported = Csv::export("maps", &parse_entries("maps", TRANSLATION))
+            .replace("Hello\nworld", "Hello\nthere");
+        let mut translations: HashMap<_, _> =
+            Csv::parse(&exported, &localization)
+                .unwrap()
+                .into_iter()
+                .collect();
+
+        let template = untranslated(TRANSLATION);
+        let (content, applied, _) =
+            apply_translations("maps", &template, &mut translations);
+
+        // Every `Hello\#world` line stays untranslated, and is reported.
+        assert_eq!(applied, 3);
+        assert_eq!(translations.len(), 3);
+        assert!(
+            translations
+                .keys()
+                .all(|(_, source)| source == "Hello\nthere")
+        );
+        assert_eq!(content.matches("Hello\\#world<#>\r\n").count(), 3);
+    }
+
+    #[test]
+    fn requires_columns() {
+        let error = Csv::parse(
+            "context,translation\nmaps,Привет\n",
+            &Localization::new(Language::English),
+        )
+        .unwrap_err();
+
+        assert!(error.to_string().starts_with("`source`"));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{localization::*, output::*};
+use anyhow::Result;
+use rvpacker_lib::constants::{COMMENT_PREFIX, NEW_LINE, SEPARATOR};
+use serde::Serialize;
+use std::{
+    fs::{read_dir, read_to_string},
+    ops::AddAssign,
+    path::Path,
+};
+use tracing::info;
+
+/// Translation progress of a single file, or a group of files.
+#[derive(Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// Count of source lines.
+    pub total: usize,
+    /// Count of source lines with non-empty translation.
+    pub translated: usize,
+    /// Count of characters in untranslated source lines.
+    pub untranslated_chars: usize,
+    /// Count of words in untranslated source lines.
+    pub untranslated_words: usize,
+}
+
+impl AddAssign for Stats {
+    fn add_assign(&mut self, other: Self) {
+        self.total += other.total;
+        self.translated += other.translated;
+        self.untranslated_chars += other.untranslated_chars;
+        self.untranslated_words += other.untranslated_words;
+    }
+}
+
+impl Stats {
+    /// Parses the contents of a translation file.
+    ///
+    /// Comment lines, such as map and event names or system entry labels,
+    /// aren't counted, since they don't need translation.
+    pub fn parse(content: &str) -> Self {
+        let mut stats = Self::default();
+
+        for line in content.lines() {
+            if line.starts_with(COMMENT_PREFIX) {
+                continue;
+            }
+
+            let Some((source, translation)) = line.split_once(SEPARATOR) else {
+                continue;
+            };
+
+            if source.is_empty() {
+                continue;
+            }
+
+            stats.total += 1;
+
+            if !translation.trim().is_empty() {
+                stats.translated += 1;
+                continue;
+            }
+
+            let source = source.replace(NEW_LINE, "\n");
+            stats.untranslated_chars += source.chars().count();
+            stats.untranslated_words += source.split_whitespace().count();
+        }
+
+        stats
+    }
+
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.translated as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Returns the category of the translation file, named the same way as in
+/// `--disable-processing` argument.
+pub fn get_file_category(filename: &str) -> &'static str {
+    match filename {
+        "system.txt" => "system",
+        "scripts.txt" | "plugins.txt" => "scripts",
+        _ if filename.starts_with("map") => "maps",
+        _ => "other",
+    }
+}
+
+/// Parses every `.txt` file in translation directory, sorted by file name.
+pub fn collect_stats(translation_path: &Path) -> Result<Vec<(String, Stats)>> {
+    let mut files = Vec::new();
+
+    for entry in read_dir(translation_path)?.flatten() {
+        let path = entry.path();
+
+        if path.extension().is_some_and(|ext| ext == "txt") {
+            files.push((
+                entry.file_name().to_string_lossy().into_owned(),
+                Stats::parse(&read_to_string(&path)?),
+            ));
+        }
+    }
+
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(files)
+}
+
+#[derive(Serialize)]
+struct StatsRecord<'a> {
+    file: Option<&'a str>,
+    category: Option<&'a str>,
+    #[serde(flatten)]
+    stats: &'a Stats,
+    percentage: f64,
+}
+
+/// Logs the stats of every file, every category and the total, or prints
+/// them as JSON events to stdout.
+pub fn report_stats(
+    files: &[(String, Stats)],
+    loc: &Localization,
+    output_format: OutputFormat,
+) {
+    let mut categories: Vec<(&str, Stats)> =
+        ["maps", "other", "system", "scripts"]
+            .into_iter()
+            .map(|category| (category, Stats::default()))
+            .collect();
+    let mut total = Stats::default();
+
+    for (file, stats) in files {
+        let category = get_file_category(file);
+
+        if let Some((_, category_stats)) =
+            categories.iter_mut().find(|(name, _)| *name == category)
+        {
+            *category_stats += *stats;
+        }
+
+        total += *stats;
+    }
+
+    let report = |event_type: &'static str,
+                  file: Option<&str>,
+                  category: Option<&str>,
+                  stats: &Stats| {
+        if output_format.is_json() {
+            let record = StatsRecord {
+                file,
+                category,
+                stats,
+                percentage: stats.percentage(),
+            };
+
+            emit_event(event_type, &record, None);
+            return;
+        }
+
+        let subject = file.or(category).unwrap_or(loc.stats_total_label);
+
+        info!(
+            "{subject}: {}/{} ({:.2}%), {} {} {}, {} {}",
+            stats.translated,
+            stats.total,
+            stats.percentage(),
+            loc.stats_untranslated_label,
+            stats.untranslated_chars,
+            loc.stats_chars_label,
+            stats.untranslated_words,
+            loc.stats_words_label,
+        );
+    };
+
+    for (file, stats) in files {
+        report("file", Some(file), Some(get_file_category(file)), stats);
+    }
+
+    for (category, stats) in &categories {
+        if stats.total != 0 {
+            report("category", None, Some(category), stats);
+        }
+    }
+
+    report("total", None, None, &total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_translated_and_untranslated_lines() {
+        let stats = Stats::parse(
+            "<!-- Map Name --><#>Town\n\
+             Hello<#>Привет\n\
+             Two words\\#more<#>\n\
+             Spaces<#>  \n\
+             <#>Empty source\n\
+             No delimiter\n",
+        );
+
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.translated, 1);
+        // `\#` counts as a single line break.
+        assert_eq!(stats.untranslated_chars, "Two words\nmore".len() + 6);
+        assert_eq!(stats.untranslated_words, 4);
+        assert!((stats.percentage() - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn treats_empty_file_as_translated() {
+        let stats = Stats::parse("<!-- Map Name --><#>Town\n");
+
+        assert_eq!(stats.total, 0);
+        assert_eq!(stats.percentage(), 100.0);
+    }
+
+    #[test]
+    fn groups_files_by_category() {
+        for (file, category) in [
+            ("map1.txt", "maps"),
+            ("maps.txt", "maps"),
+            ("system.txt", "system"),
+            ("scripts.txt", "scripts"),
+            ("plugins.txt", "scripts"),
+            ("actors.txt", "other"),
+        ] {
+            assert_eq!(get_file_category(file), category, "{file}");
+        }
+    }
+
+    #[test]
+    fn collects_only_txt_files_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("system.txt"), "a<#>b\n").unwrap();
+        std::fs::write(dir.path().join("actors.txt"), "a<#>\n").unwrap();
+        std::fs::write(dir.path().join(".rvpacker-metadata"), "{}").unwrap();
+
+        let stats = collect_stats(dir.path()).unwrap();
+        let files: Vec<&str> =
+            stats.iter().map(|(file, _)| file.as_str()).collect();
+
+        assert_eq!(files, ["actors.txt", "system.txt"]);
+        assert_eq!(stats[0].1.translated, 0);
+        assert_eq!(stats[1].1.translated, 1);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{cache::CATEGORIES, localization::*, output::*, stats::*};
+use anyhow::Result;
+use rvpacker_lib::{
+    constants::{COMMENT_PREFIX, NEW_LINE, SEPARATOR},
+    types::FileFlags,
+};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+use tracing::warn;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub enum FindingKind {
+    MissingDelimiter,
+    DuplicateDelimiter,
+    MissingSourceFile,
+    NotInSource,
+    ControlCodeMismatch,
+    LineBreakMismatch,
+    LeftoverSourceScript,
+}
+
+impl FindingKind {
+    fn message(self, loc: &Localization) -> &'static str {
+        match self {
+            Self::MissingDelimiter => loc.missing_delimiter_msg,
+            Self::DuplicateDelimiter => loc.duplicate_delimiter_msg,
+            Self::MissingSourceFile => loc.missing_source_file_msg,
+            Self::NotInSource => loc.line_not_in_source_msg,
+            Self::ControlCodeMismatch => loc.control_code_mismatch_msg,
+            Self::LineBreakMismatch => loc.line_break_mismatch_msg,
+            Self::LeftoverSourceScript => loc.leftover_source_script_msg,
+        }
+    }
+}
+
+/// A single problem in a translation file.
+#[derive(Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub file: String,
+    /// 1-based line number, or `0` if finding is related to the whole file.
+    pub line: usize,
+    pub details: Option<String>,
+}
+
+impl Report for Finding {
+    const EVENT_TYPE: &str = "finding";
+
+    fn message(&self, loc: &Localization) -> &'static str {
+        self.kind.message(loc)
+    }
+
+    fn log(&self, message: &str, _: &Localization) {
+        match &self.details {
+            Some(details) => {
+                warn!("{}:{}: {message} ({details})", self.file, self.line)
+            }
+            None => warn!("{}:{}: {message}", self.file, self.line),
+        }
+    }
+}
+
+/// Returns RPG Maker control codes (`\C[n]`, `\N[n]`, `\V[n]`, `\I[n]`, `\{`
+/// and `\}`) used in the text, sorted and uppercased.
+fn get_control_codes(text: &str) -> Vec<String> {
+    let mut codes = Vec::new();
+    let mut rest = text;
+
+    while let Some(index) = rest.find('\\') {
+        rest = &rest[index + 1..];
+        let mut chars = rest.chars();
+
+        match chars.next() {
+            Some(brace @ ('{' | '}')) => codes.push(format!("\\{brace}")),
+            Some(code @ ('C' | 'N' | 'V' | 'I' | 'c' | 'n' | 'v' | 'i')) => {
+                let argument = chars
+                    .as_str()
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.split_once(']'))
+                    .map(|(argument, _)| argument)
+                    .filter(|argument| {
+                        !argument.is_empty()
+                            && argument.chars().all(|c| c.is_ascii_digit())
+                    });
+
+                if let Some(argument) = argument {
+                    codes.push(format!(
+                        "\\{}[{argument}]",
+                        code.to_ascii_uppercase()
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    codes.sort_unstable();
+    codes
+}
+
+/// Whether the character belongs to Japanese, Chinese or Korean scripts.
+const fn is_cjk(char: char) -> bool {
+    matches!(
+        char,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{31F0}'..='\u{31FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{FF66}'..='\u{FF9F}'
+            | '\u{1100}'..='\u{11FF}'
+            | '\u{AC00}'..='\u{D7AF}'
+    )
+}
+
+/// Validates a single translation file against the source lines, that were
+/// read from the game files.
+///
+/// `source_lines` is `None`, if the file doesn't exist in the source data.
+fn validate_file(
+    file: &str,
+    content: &str,
+    source_lines: Option<&HashSet<String>>,
+    findings: &mut Vec<Finding>,
+) {
+    let mut push = |kind, line, details| {
+        findings.push(Finding {
+            kind,
+            file: file.into(),
+            line,
+            details,
+        })
+    };
+
+    if source_lines.is_none() {
+        push(FindingKind::MissingSourceFile, 0, None);
+    }
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+
+        // Blank lines are skipped when the file is parsed, so they're not
+        // missing anything.
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some((source, translation)) = line.split_once(SEPARATOR) else {
+            push(FindingKind::MissingDelimiter, line_number, None);
+            continue;
+        };
+
+        if translation.contains(SEPARATOR) {
+            push(FindingKind::DuplicateDelimiter, line_number, None);
+            continue;
+        }
+
+        if line.starts_with(COMMENT_PREFIX) {
+            continue;
+        }
+
+        if source_lines.is_some_and(|lines| !lines.contains(source)) {
+            push(FindingKind::NotInSource, line_number, None);
+        }
+
+        if translation.trim().is_empty() {
+            continue;
+        }
+
+        let source_codes = get_control_codes(source);
+        let translation_codes = get_control_codes(translation);
+
+        if source_codes != translation_codes {
+            push(
+                FindingKind::ControlCodeMismatch,
+                line_number,
+                Some(format!(
+                    "{} => {}",
+                    source_codes.join(" "),
+                    translation_codes.join(" ")
+                )),
+            );
+        }
+
+        let source_breaks = source.matches(NEW_LINE).count();
+        let translation_breaks = translation.matches(NEW_LINE).count();
+
+        if source_breaks != translation_breaks {
+            push(
+                FindingKind::LineBreakMismatch,
+                line_number,
+                Some(format!("{source_breaks} => {translation_breaks}")),
+            );
+        }
+
+        if source.chars().any(is_cjk) {
+            let leftover: String =
+                translation.chars().filter(|&c| is_cjk(c)).collect();
+
+            if !leftover.is_empty() {
+                push(
+                    FindingKind::LeftoverSourceScript,
+                    line_number,
+                    Some(leftover),
+                );
+            }
+        }
+    }
+}
+
+/// Returns source parts of non-comment lines of the translation file.
+fn get_source_lines(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with(COMMENT_PREFIX))
+        .filter_map(|line| line.split_once(SEPARATOR))
+        .map(|(source, _)| source.to_string())
+        .collect()
+}
+
+/// Validates every `.txt` file of `file_flags` categories in translation
+/// directory against the files, freshly read from the game data to
+/// `source_translation_path`.
+///
+/// Files of other categories are skipped, since they aren't read from the
+/// game data.
+pub fn validate(
+    translation_path: &Path,
+    source_translation_path: &Path,
+    file_flags: FileFlags,
+) -> Result<Vec<Finding>> {
+    let mut files = Vec::new();
+
+    for entry in read_dir(translation_path)?.flatten() {
+        let path = entry.path();
+
+        if path.extension().is_none_or(|ext| ext != "txt") {
+            continue;
+        }
+
+        let file = entry.file_name().to_string_lossy().into_owned();
+        let category = get_file_category(&file);
+
+        if CATEGORIES
+            .into_iter()
+            .any(|(flag, name)| name == category && !file_flags.contains(flag))
+        {
+            continue;
+        }
+
+        files.push(file);
+    }
+
+    files.sort_unstable();
+
+    let mut findings = Vec::new();
+
+    for file in files {
+        let content = read_to_string(translation_path.join(&file))?;
+        let source_lines = read_to_string(source_translation_path.join(&file))
+            .ok()
+            .map(|content| get_source_lines(&content));
+
+        validate_file(&file, &content, source_lines.as_ref(), &mut findings);
+    }
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+
+    fn findings(
+        content: &str,
+        source: Option<&str>,
+    ) -> Vec<(FindingKind, usize)> {
+        let source_lines = source.map(get_source_lines);
+        let mut findings = Vec::new();
+
+        validate_file(
+            "map1.txt",
+            content,
+            source_lines.as_ref(),
+            &mut findings,
+        );
+        findings
+            .into_iter()
+            .map(|finding| (finding.kind, finding.line))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_control_codes() {
+        assert_eq!(
+            get_control_codes(r"\c[2]Hi \N[1], \V[10]\I[64]\{\}\C[0]"),
+            [
+                r"\C[0]", r"\C[2]", r"\I[64]", r"\N[1]", r"\V[10]", r"\{",
+                r"\}"
+            ]
+        );
+
+        // Not control codes: unknown letters, missing or non-numeric
+        // arguments, and line breaks.
+        assert!(get_control_codes(r"\X[1] \C \C[] \N[a] \#").is_empty());
+    }
+
+    #[test]
+    fn finds_control_code_and_line_break_mismatches() {
+        let content = "\\C[2]Hi\\#there<#>\\C[2]Привет\\#всем\n\
+                       \\N[1] wins<#>Побеждает\n\
+                       Two\\#lines<#>Одна строка\n\
+                       \\V[1]<#>\n";
+
+        assert_eq!(
+            findings(content, Some(content)),
+            [
+                (FindingKind::ControlCodeMismatch, 2),
+                (FindingKind::LineBreakMismatch, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_leftover_source_script() {
+        let content = "こんにちは<#>Hello\n\
+                       勇者<#>勇者 Hero\n\
+                       Hello<#>こんにちは\n";
+
+        let mut findings = Vec::new();
+        validate_file(
+            "map1.txt",
+            content,
+            Some(&get_source_lines(content)),
+            &mut findings,
+        );
+
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].kind == FindingKind::LeftoverSourceScript);
+        assert_eq!(findings[0].line, 2);
+        assert_eq!(findings[0].details.as_deref(), Some("勇者"));
+    }
+
+    #[test]
+    fn finds_broken_delimiters_and_unknown_lines() {
+        let source = "<!-- Map --><#>1\nHello<#>\n";
+        let content = "<!-- Map --><#>1\n\
+                       Hello<#>Привет<#>\n\
+                       Hello Привет\n\
+                       \n\
+                       Bye<#>Пока\n";
+
+        assert_eq!(
+            findings(content, Some(source)),
+            [
+                (FindingKind::DuplicateDelimiter, 2),
+                (FindingKind::MissingDelimiter, 3),
+                (FindingKind::NotInSource, 5)
+            ]
+        );
+        assert_eq!(
+            findings(source, None),
+            [(FindingKind::MissingSourceFile, 0)]
+        );
+    }
+
+    #[test]
+    fn skips_disabled_categories() {
+        let translation = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+
+        for file in ["maps.txt", "system.txt"] {
+            write(translation.path().join(file), "Hello<#>\n").unwrap();
+            write(source.path().join(file), "Bye<#>\n").unwrap();
+        }
+
+        let findings =
+            validate(translation.path(), source.path(), FileFlags::System)
+                .unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].file, "system.txt");
+    }
+}
@@ -0,0 +1,76 @@
+use crate::localization::*;
+use anyhow::Result;
+use notify::{Event, RecursiveMode, Watcher, recommended_watcher};
+use std::{
+    collections::BTreeSet,
+    path::Path,
+    sync::mpsc::{RecvTimeoutError, channel},
+    time::Duration,
+};
+use tracing::{error, info};
+
+/// Time, during which further changes are collected after the first one.
+///
+/// Editors often produce several events for a single save, and several files
+/// may be saved at once, so they all result in a single rebuild.
+const DEBOUNCE_TIME: Duration = Duration::from_millis(300);
+
+/// Adds names of the `.txt` files, affected by the event, to `changed`.
+fn collect_changed(
+    event: notify::Result<Event>,
+    changed: &mut BTreeSet<String>,
+) -> Result<()> {
+    let event = event?;
+
+    if event.kind.is_access() {
+        return Ok(());
+    }
+
+    for path in event.paths {
+        if path.extension().is_some_and(|ext| ext == "txt") {
+            if let Some(name) = path.file_name() {
+                changed.insert(name.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Watches `.txt` files in `path`, and calls `on_change` with the names of
+/// changed files, once the changes settle.
+///
+/// Errors, returned by `on_change`, are logged, and watching continues. Blocks
+/// until the process is interrupted.
+pub fn watch(
+    path: &Path,
+    localization: &Localization,
+    mut on_change: impl FnMut(&BTreeSet<String>) -> Result<()>,
+) -> Result<()> {
+    let (sender, receiver) = channel();
+    let mut watcher = recommended_watcher(sender)?;
+    watcher.watch(path, RecursiveMode::NonRecursive)?;
+
+    info!("{}: {}", path.display(), localization.watching_msg);
+
+    loop {
+        let mut changed = BTreeSet::new();
+        collect_changed(receiver.recv()?, &mut changed)?;
+
+        loop {
+            match receiver.recv_timeout(DEBOUNCE_TIME) {
+                Ok(event) => collect_changed(event, &mut changed)?,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+
+        if changed.is_empty() {
+            continue;
+        }
+
+        if let Err(err) = on_change(&changed) {
+            error!("{err:#}");
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{exchange::*, localization::*, metadata::*};
+use anyhow::{Context, Result};
+use quick_xml::{
+    Reader,
+    escape::{escape, resolve_predefined_entity},
+    events::{BytesStart, Event},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string_pretty};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    fs::{create_dir_all, read_to_string, write},
+    mem::take,
+    path::Path,
+};
+use tracing::{info, warn};
+
+pub const XLIFF_FILE_NAME: &str = "translation.xlf";
+
+/// File in translation directory, that stores states of XLIFF segments
+/// between imports and exports, since translation files can't hold them.
+pub const XLIFF_STATE_FILE: &str = ".rvpacker-xliff-state";
+
+/// State of XLIFF segment.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    #[default]
+    Initial,
+    Translated,
+    Reviewed,
+    Final,
+}
+
+impl State {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Initial => "initial",
+            Self::Translated => "translated",
+            Self::Reviewed => "reviewed",
+            Self::Final => "final",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "initial" => Self::Initial,
+            "translated" => Self::Translated,
+            "reviewed" => Self::Reviewed,
+            "final" => Self::Final,
+            _ => return None,
+        })
+    }
+}
+
+/// States of the segments by file name, context and source text.
+type States = BTreeMap<String, BTreeMap<String, BTreeMap<String, State>>>;
+
+fn load_states(translation_path: &Path) -> Result<States> {
+    match read_to_string(translation_path.join(XLIFF_STATE_FILE)) {
+        Ok(content) => Ok(from_str(&content)?),
+        Err(_) => Ok(States::new()),
+    }
+}
+
+/// Escapes the text for XML, including carriage returns, which would be
+/// normalized by parsers otherwise.
+fn escape_text(text: &str) -> String {
+    escape(text).replace('\r', "&#13;")
+}
+
+/// Exports every translation file to a single XLIFF 2.0 document with one
+/// `<file>` per translation file, and one `<unit>` per entry.
+///
+/// Translation options from metadata are stored as notes of every `<file>`.
+pub fn export_xliff(
+    translation_path: &Path,
+    output_path: &Path,
+    metadata: Option<&Metadata>,
+    localization: &Localization,
+) -> Result<()> {
+    let states = load_states(translation_path)?;
+    let mut output = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="und">
+"#,
+    );
+
+    for name in get_file_names(translation_path, "txt")? {
+        let content =
+            read_to_string(translation_path.join(format!("{name}.txt")))?;
+        let file_states = states.get(&name);
+
+        let _ = writeln!(
+            output,
+            r#"  <file id="{}" xml:space="preserve">"#,
+            escape_text(&name)
+        );
+
+        if let Some(metadata) = metadata {
+            let _ = writeln!(
+                output,
+                r#"    <notes>
+      <note category="romanize">{}</note>
+      <note category="trim">{}</note>
+    </notes>"#,
+                metadata.romanize, metadata.trim
+            );
+        }
+
+        for (index, entry) in parse_entries(&name, &content).iter().enumerate()
+        {
+            let state = if entry.translation.is_empty() {
+                State::Initial
+            } else {
+                file_states
+                    .and_then(|contexts| contexts.get(&entry.context))
+                    .and_then(|sources| sources.get(&entry.source))
+                    .copied()
+                    .unwrap_or(State::Translated)
+            };
+
+            let _ = writeln!(
+                output,
+                r#"    <unit id="u{}" name="{}">
+      <notes>
+        <note category="location">{name}.txt:{}</note>
+      </notes>
+      <segment state="{}">
+        <source>{}</source>"#,
+                index + 1,
+                escape_text(&entry.context),
+                entry.line,
+                state.name(),
+                escape_text(&entry.source),
+            );
+
+            if !entry.translation.is_empty() {
+                let _ = writeln!(
+                    output,
+                    "        <target>{}</target>",
+                    escape_text(&entry.translation)
+                );
+            }
+
+            output.push_str("      </segment>\n    </unit>\n");
+        }
+
+        output.push_str("  </file>\n");
+    }
+
+    output.push_str("</xliff>\n");
+
+    create_dir_all(output_path)?;
+    let output_file_path = output_path.join(XLIFF_FILE_NAME);
+    write(&output_file_path, output)?;
+
+    info!(
+        "{}: {}",
+        output_file_path.display(),
+        localization.exported_file_msg
+    );
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Unit {
+    context: String,
+    source: String,
+    /// `None`, if the unit has no `<target>`, so its translation is kept.
+    target: Option<String>,
+    state: Option<State>,
+}
+
+fn get_attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    Ok(element
+        .try_get_attribute(name)?
+        .map(|attribute| attribute.unescape_value())
+        .transpose()?
+        .map(|value| value.into_owned()))
+}
+
+/// Parses XLIFF document to the list of files with their units.
+fn parse_xliff(content: &str) -> Result<Vec<(String, Vec<Unit>)>> {
+    let mut reader = Reader::from_str(content);
+    let mut files: Vec<(String, Vec<Unit>)> = Vec::new();
+    let mut unit = Unit::default();
+    // Whether the text of `<source>` or `<target>` is being read.
+    let mut capture = false;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"file" => files.push((
+                    get_attribute(&element, "id")?.unwrap_or_default(),
+                    Vec::new(),
+                )),
+                b"unit" => {
+                    unit = Unit {
+                        context: get_attribute(&element, "name")?
+                            .unwrap_or_default(),
+                        ..Default::default()
+                    }
+                }
+                b"segment" => {
+                    unit.state = get_attribute(&element, "state")?
+                        .and_then(|state| State::from_name(&state))
+                        .or(unit.state);
+                }
+                b"source" | b"target" => capture = true,
+                _ => {}
+            },
+            Event::End(element) => match element.local_name().as_ref() {
+                b"source" => {
+                    unit.source.push_str(&take(&mut text));
+                    capture = false;
+                }
+                b"target" => {
+                    unit.target
+                        .get_or_insert_default()
+                        .push_str(&take(&mut text));
+                    capture = false;
+                }
+                b"unit" => {
+                    if let Some((_, units)) = files.last_mut() {
+                        units.push(take(&mut unit));
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(element)
+                if element.local_name().as_ref() == b"target" =>
+            {
+                unit.target.get_or_insert_default();
+            }
+            Event::Text(content) if capture => {
+                text.push_str(&content.xml_content()?);
+            }
+            Event::CData(content) if capture => {
+                text.push_str(&content.decode()?);
+            }
+            Event::GeneralRef(reference) if capture => {
+                if let Some(char) = reference.resolve_char_ref()? {
+                    text.push(char);
+                } else if let Some(resolved) =
+                    resolve_predefined_entity(&reference.decode()?)
+                {
+                    text.push_str(resolved);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+/// Imports translations from XLIFF document to the corresponding translation
+/// files, and stores states of the segments for the next export.
+pub fn import_xliff(
+    input_path: &Path,
+    translation_path: &Path,
+    localization: &Localization,
+) -> Result<()> {
+    let input_file_path = input_path.join(XLIFF_FILE_NAME);
+
+    let content = read_to_string(&input_file_path).with_context(|| {
+        format!(
+            "{}: {}",
+            input_file_path.display(),
+            localization.exchange_dir_missing_msg
+        )
+    })?;
+
+    let files = parse_xliff(&content).with_context(|| {
+        format!(
+            "{}: {}",
+            input_file_path.display(),
+            localization.exchange_parse_failed_msg
+        )
+    })?;
+
+    let mut states = load_states(translation_path)?;
+
+    for (name, units) in files {
+        let translation_file_path =
+            translation_path.join(format!("{name}.txt"));
+
+        let Ok(content) = read_to_string(&translation_file_path) else {
+            warn!(
+                "{}: {}",
+                translation_file_path.display(),
+                localization.exchange_translation_file_missing_msg
+            );
+            continue;
+        };
+
+        let file_states = states.entry(name.clone()).or_default();
+        file_states.clear();
+
+        let mut translations = HashMap::new();
+
+        for unit in units {
+            if let Some(state) = unit.state {
+                file_states
+                    .entry(unit.context.clone())
+                    .or_default()
+                    .insert(unit.source.clone(), state);
+            }
+
+            if let Some(target) = unit.target {
+                translations.insert((unit.context, unit.source), target);
+            }
+        }
+
+        import_translations(
+            &name,
+            &translation_file_path,
+            &input_file_path,
+            &content,
+            translations,
+            localization,
+        )?;
+    }
+
+    write(
+        translation_path.join(XLIFF_STATE_FILE),
+        to_string_pretty(&states)?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::tests::*;
+
+    #[test]
+    fn round_trips_translation_file() {
+        let localization = Localization::new(Language::English);
+        let translation_dir = tempfile::tempdir().unwrap();
+        let xliff_dir = tempfile::tempdir().unwrap();
+        let maps_path = translation_dir.path().join("maps.txt");
+
+        write(&maps_path, TRANSLATION).unwrap();
+        export_xliff(
+            translation_dir.path(),
+            xliff_dir.path(),
+            None,
+            &localization,
+        )
+        .unwrap();
+
+        write(&maps_path, untranslated(TRANSLATION)).unwrap();
+        import_xliff(xliff_dir.path(), translation_dir.path(), &localization)
+            .unwrap();
+
+        assert_eq!(read_to_string(&maps_path).unwrap(), TRANSLATION);
+    }
+
+    #[test]
+    fn keeps_translations_of_units_without_target() {
+        let localization = Localization::new(Language::English);
+        let translation_dir = tempfile::tempdir().unwrap();
+        let maps_path = translation_dir.path().join("maps.txt");
+
+        write(&maps_path, "Hello<#>Привет\nWorld<#>Мир\nBye<#>Пока\n").unwrap();
+        write(
+            translation_dir.path().join(XLIFF_FILE_NAME),
+            r#"<xliff version="2.0"><file id="maps">
+<unit name="maps"><segment state="reviewed"><source>Hello</source></segment></unit>
+<unit name="maps"><segment><source>World</source><target/></segment></unit>
+<unit name="maps"><segment><source>Bye</source><target>Прощай</target></segment></unit>
+</file></xliff>"#,
+        )
+        .unwrap();
+
+        import_xliff(
+            translation_dir.path(),
+            translation_dir.path(),
+            &localization,
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_to_string(&maps_path).unwrap(),
+            "Hello<#>Привет\nWorld<#>\nBye<#>Прощай\n"
+        );
+    }
+}